@@ -0,0 +1,154 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # `ClientBuilder`
+//! Configures and opens connections with a server.
+
+use crate::{Client, ClientError, CommandSink, EventStream, frame::read_frame};
+use protocol::{Compression, Status};
+use quinn::{
+    ClientConfig, Endpoint, VarInt,
+    crypto::rustls::QuicClientConfig,
+    rustls::{self, RootCertStore, crypto::ring, version::TLS13},
+};
+use rustls_pki_types::{CertificateDer, pem::PemObject};
+use std::{net::SocketAddr, path::Path, sync::Arc};
+use tracing::info;
+use wire::{Codec, STATUS_ALPN, compression::Dictionary};
+
+/// Builds a [`Client`], or asks a server for its [`Status`]
+#[derive(Debug, Clone)]
+pub struct ClientBuilder {
+    codec: Codec,
+    compression: Option<Compression>,
+    dictionary: Option<Dictionary>,
+    roots: RootCertStore,
+    bind: Option<SocketAddr>,
+}
+
+impl Default for ClientBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClientBuilder {
+    /// Creates a builder using msgpack, without compression and without any
+    /// trusted certificates
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            codec: Codec::default(),
+            compression: None,
+            dictionary: None,
+            roots: RootCertStore::empty(),
+            bind: None,
+        }
+    }
+
+    /// Uses the given codec for all commands and events
+    #[must_use]
+    pub const fn with_codec(mut self, codec: Codec) -> Self {
+        self.codec = codec;
+        self
+    }
+
+    /// Asks the server to compress the events it sends
+    #[must_use]
+    pub const fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    /// Offers the server a compression dictionary, it is only used if the
+    /// server has the same one
+    #[must_use]
+    pub fn with_dictionary(mut self, dictionary: Dictionary) -> Self {
+        self.dictionary = Some(dictionary);
+        self
+    }
+
+    /// Trusts the given certificate, for example the self-signed certificate of the server
+    ///
+    /// # Errors
+    /// Returns a `ClientError` when the certificate is invalid.
+    pub fn with_certificate(mut self, cert: CertificateDer<'static>) -> Result<Self, ClientError> {
+        self.roots.add(cert)?;
+        Ok(self)
+    }
+
+    /// Trusts all certificates in a PEM file
+    ///
+    /// # Errors
+    /// Returns a `ClientError` when the file can't be read or contains an invalid certificate.
+    pub fn with_certificates_from_file<P: AsRef<Path>>(self, path: P) -> Result<Self, ClientError> {
+        CertificateDer::pem_file_iter(path)?
+            .try_fold(self, |builder, cert| builder.with_certificate(cert?))
+    }
+
+    /// Binds the local socket to the given address, instead of an unspecified one
+    #[must_use]
+    pub const fn with_bind_address(mut self, addr: SocketAddr) -> Self {
+        self.bind = Some(addr);
+        self
+    }
+
+    /// Connects to a server and opens the stream carrying commands and events.
+    ///
+    /// The `server_name` has to match the certificate of the server.
+    ///
+    /// # Errors
+    /// Returns a `ClientError` when the connection can't be established.
+    pub async fn connect(self, addr: SocketAddr, server_name: &str) -> Result<Client, ClientError> {
+        let endpoint = self.endpoint(addr, self.codec.alpn())?;
+        let connection = endpoint.connect(addr, server_name)?.await?;
+        info!("connected to {addr} using the {} codec", self.codec.name());
+
+        let (send, recv) = connection.open_bi().await?;
+        let dictionary = self.dictionary.as_ref().map(Dictionary::id);
+        Ok(Client::new(
+            CommandSink::new(send, self.codec, connection, endpoint),
+            EventStream::new(recv, self.codec, self.dictionary),
+            self.compression,
+            dictionary,
+        ))
+    }
+
+    /// Asks a server for its status without joining
+    ///
+    /// # Errors
+    /// Returns a `ClientError` when the connection fails or the status is invalid.
+    pub async fn status(&self, addr: SocketAddr, server_name: &str) -> Result<Status, ClientError> {
+        let endpoint = self.endpoint(addr, STATUS_ALPN)?;
+        let connection = endpoint.connect(addr, server_name)?.await?;
+
+        let mut recv = connection.accept_uni().await?;
+        let frame = read_frame(&mut recv)
+            .await?
+            .ok_or(ClientError::Disconnected)?;
+        connection.close(VarInt::from_u32(0), b"status received");
+
+        Ok(Codec::MessagePack.decode(&frame.payload)?)
+    }
+
+    fn endpoint(&self, addr: SocketAddr, alpn: &[u8]) -> Result<Endpoint, ClientError> {
+        let mut crypto =
+            rustls::ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+                .with_protocol_versions(&[&TLS13])?
+                .with_root_certificates(self.roots.clone())
+                .with_no_client_auth();
+        crypto.alpn_protocols = vec![alpn.to_vec()];
+        let config = ClientConfig::new(Arc::new(QuicClientConfig::try_from(crypto)?));
+
+        let bind = self.bind.unwrap_or_else(|| {
+            if addr.is_ipv6() {
+                (std::net::Ipv6Addr::UNSPECIFIED, 0).into()
+            } else {
+                (std::net::Ipv4Addr::UNSPECIFIED, 0).into()
+            }
+        });
+        let mut endpoint = Endpoint::client(bind)?;
+        endpoint.set_default_client_config(config);
+        Ok(endpoint)
+    }
+}
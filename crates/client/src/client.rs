@@ -0,0 +1,113 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # `Client`
+//! A connection with a server.
+
+use crate::{ClientError, CommandSink, EventStream};
+use protocol::{
+    Compression,
+    command::{CommandKind, join::Join},
+    event::{EventKind, JoinAccept},
+};
+use std::net::SocketAddr;
+use tracing::debug;
+use wire::capture::Record;
+
+/// A connection with a server, created with [`ClientBuilder::connect`](crate::ClientBuilder::connect)
+#[derive(Debug)]
+pub struct Client {
+    commands: CommandSink,
+    events: EventStream,
+    compression: Option<Compression>,
+    dictionary: Option<u32>,
+}
+
+impl Client {
+    pub(crate) const fn new(
+        commands: CommandSink,
+        events: EventStream,
+        compression: Option<Compression>,
+        dictionary: Option<u32>,
+    ) -> Self {
+        Self {
+            commands,
+            events,
+            compression,
+            dictionary,
+        }
+    }
+
+    /// Joins the server and waits until the server accepted it.
+    ///
+    /// Asks for the compression and dictionary configured on the builder.
+    /// Wrap it in [`tokio::time::timeout`] to limit how long to wait.
+    ///
+    /// # Errors
+    /// Returns a `ClientError` when the connection fails, or
+    /// [`ClientError::Disconnected`] when the server closes the stream first.
+    pub async fn join(&mut self, uuid: u64, hash: u64) -> Result<JoinAccept, ClientError> {
+        self.send(Join {
+            uuid,
+            hash,
+            ip: None,
+            connection: None,
+            compression: self.compression,
+            dictionary: self.dictionary,
+        })
+        .await?;
+
+        loop {
+            match self.recv().await? {
+                Some(EventKind::JoinAccept(accept)) if accept.uuid == uuid => return Ok(accept),
+                Some(event) => debug!("ignoring {event:?} while joining"),
+                None => return Err(ClientError::Disconnected),
+            }
+        }
+    }
+
+    /// Sends a command to the server
+    ///
+    /// # Errors
+    /// Returns a `ClientError` when the command can't be encoded or written.
+    pub async fn send(&mut self, command: impl Into<CommandKind>) -> Result<(), ClientError> {
+        self.commands.send(command).await
+    }
+
+    /// Sends the commands of a recorded session again, see [`CommandSink::replay`]
+    ///
+    /// # Errors
+    /// Returns a `ClientError` when a command can't be encoded or written.
+    pub async fn replay<'a>(
+        &mut self,
+        records: impl IntoIterator<Item = &'a Record>,
+        session: u64,
+    ) -> Result<usize, ClientError> {
+        self.commands.replay(records, session).await
+    }
+
+    /// Waits for the next event, returns `None` when the server closed the stream.
+    ///
+    /// # Errors
+    /// Returns a `ClientError` when the frame is invalid or can't be decoded.
+    pub async fn recv(&mut self) -> Result<Option<EventKind>, ClientError> {
+        self.events.recv().await
+    }
+
+    /// Splits the client, so commands and events can be handled by different tasks
+    #[must_use]
+    pub fn split(self) -> (CommandSink, EventStream) {
+        (self.commands, self.events)
+    }
+
+    /// The address of the server
+    #[must_use]
+    pub fn remote_address(&self) -> SocketAddr {
+        self.commands.connection().remote_address()
+    }
+
+    /// Closes the connection with the server
+    pub fn close(&self) {
+        self.commands.close();
+    }
+}
@@ -0,0 +1,99 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # `CommandSink`
+//! The sending half of a connection.
+
+use crate::{ClientError, frame::write_frame};
+use protocol::command::CommandKind;
+use quinn::{Connection, Endpoint, SendStream, VarInt};
+use tokio::time::Instant;
+use tracing::trace;
+use wire::{
+    Codec,
+    capture::{Message, Record},
+};
+
+/// Sends commands to the server.
+///
+/// Owns the connection, so the connection is closed once this is dropped.
+#[derive(Debug)]
+pub struct CommandSink {
+    send: SendStream,
+    codec: Codec,
+    connection: Connection,
+    // keeps the endpoint driving the connection alive
+    _endpoint: Endpoint,
+}
+
+impl CommandSink {
+    pub(crate) const fn new(
+        send: SendStream,
+        codec: Codec,
+        connection: Connection,
+        endpoint: Endpoint,
+    ) -> Self {
+        Self {
+            send,
+            codec,
+            connection,
+            _endpoint: endpoint,
+        }
+    }
+
+    /// Sends a command to the server
+    ///
+    /// # Errors
+    /// Returns a `ClientError` when the command can't be encoded or written.
+    pub async fn send(&mut self, command: impl Into<CommandKind>) -> Result<(), ClientError> {
+        let command = command.into();
+        trace!("sending {command:?}");
+        let payload = self.codec.encode(&command)?;
+        write_frame(&mut self.send, &payload).await
+    }
+
+    /// Sends the commands of a recorded session again, keeping the time
+    /// between them. Returns the amount of commands sent.
+    ///
+    /// The server fills in the connection details of a `Join` again, so the
+    /// recorded values don't matter.
+    ///
+    /// # Errors
+    /// Returns a `ClientError` when a command can't be encoded or written.
+    pub async fn replay<'a>(
+        &mut self,
+        records: impl IntoIterator<Item = &'a Record>,
+        session: u64,
+    ) -> Result<usize, ClientError> {
+        let start = Instant::now();
+        let mut first = None;
+        let mut sent = 0;
+
+        for record in records {
+            let Message::Inbound(command) = &record.message else {
+                continue;
+            };
+            if record.session != session {
+                continue;
+            }
+
+            let offset = *first.get_or_insert_with(|| record.elapsed());
+            tokio::time::sleep_until(start + record.elapsed().saturating_sub(offset)).await;
+            self.send(*command).await?;
+            sent += 1;
+        }
+
+        Ok(sent)
+    }
+
+    /// The underlying QUIC connection
+    #[must_use]
+    pub const fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    /// Closes the connection with the server
+    pub fn close(&self) {
+        self.connection.close(VarInt::from_u32(0), b"client closed");
+    }
+}
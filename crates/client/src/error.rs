@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # Error
+//! Defines the error type for the client crate
+
+use thiserror::Error;
+
+/// Error type used by the [`crate::Client`]
+#[derive(Debug, Error)]
+pub enum ClientError {
+    /// Error from IO
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    /// `ConnectError` from quinn, the connection couldn't be started
+    #[error("ConnectError: {0}")]
+    Connect(#[from] quinn::ConnectError),
+    /// `ConnectionError` from quinn, the connection failed or was closed
+    #[error("ConnectionError: {0}")]
+    Connection(#[from] quinn::ConnectionError),
+    /// Error while encoding a command or decoding an event
+    #[error("CodecError: {0}")]
+    Codec(#[from] wire::CodecError),
+    /// Error from rustls, for example an invalid certificate
+    #[error("TLS error: {0}")]
+    Tls(#[from] quinn::rustls::Error),
+    /// The TLS config doesn't support any cipher suite usable by QUIC
+    #[error("NoInitialCipherSuite: {0}")]
+    CipherSuite(#[from] quinn::crypto::rustls::NoInitialCipherSuite),
+    /// A certificate file couldn't be read or parsed
+    #[error("PemError: {0}")]
+    Pem(#[from] rustls_pki_types::pem::Error),
+    /// A frame is larger than the server accepts
+    #[error("frame of {0} bytes is too large")]
+    FrameTooLarge(usize),
+    /// The server sent a compressed frame before negotiating compression
+    #[error("received a compressed frame without negotiating compression")]
+    UnexpectedCompression,
+    /// The server closed the stream
+    #[error("disconnected from the server")]
+    Disconnected,
+}
@@ -0,0 +1,81 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # `EventStream`
+//! The receiving half of a connection.
+
+use crate::{ClientError, frame::read_frame};
+use protocol::{Compression, event::EventKind};
+use quinn::RecvStream;
+use tracing::trace;
+use wire::{
+    Codec,
+    compression::{Dictionary, decompress},
+    frame::MAX_MESSAGE_SIZE,
+};
+
+/// Receives events from the server.
+///
+/// Decompresses frames once a [`JoinAccept`](protocol::event::JoinAccept)
+/// enabled compression.
+#[derive(Debug)]
+pub struct EventStream {
+    recv: RecvStream,
+    codec: Codec,
+    dictionary: Option<Dictionary>,
+    compression: Option<(Compression, Option<Dictionary>)>,
+}
+
+impl EventStream {
+    pub(crate) const fn new(
+        recv: RecvStream,
+        codec: Codec,
+        dictionary: Option<Dictionary>,
+    ) -> Self {
+        Self {
+            recv,
+            codec,
+            dictionary,
+            compression: None,
+        }
+    }
+
+    /// Waits for the next event, returns `None` when the server closed the stream.
+    ///
+    /// # Errors
+    /// Returns a `ClientError` when the frame is invalid or can't be decoded.
+    pub async fn recv(&mut self) -> Result<Option<EventKind>, ClientError> {
+        let Some(frame) = read_frame(&mut self.recv).await? else {
+            return Ok(None);
+        };
+
+        let payload = if frame.compressed {
+            let Some((compression, dictionary)) = &self.compression else {
+                return Err(ClientError::UnexpectedCompression);
+            };
+            decompress(
+                *compression,
+                dictionary.as_ref(),
+                &frame.payload,
+                MAX_MESSAGE_SIZE as usize,
+            )?
+        } else {
+            frame.payload
+        };
+
+        let event: EventKind = self.codec.decode(&payload)?;
+        trace!("received {event:?}");
+
+        if let EventKind::JoinAccept(accept) = &event
+            && let Some(compression) = accept.compression
+        {
+            let dictionary = self
+                .dictionary
+                .clone()
+                .filter(|dictionary| accept.dictionary == Some(dictionary.id()));
+            self.compression = Some((compression, dictionary));
+        }
+
+        Ok(Some(event))
+    }
+}
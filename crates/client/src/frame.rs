@@ -0,0 +1,105 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # Frame
+//! Reads and writes the length-prefixed frames the server speaks.
+
+use crate::ClientError;
+use std::io;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use wire::frame::{COMPRESSED_FLAG, MAX_MESSAGE_SIZE};
+
+/// A frame read from the stream
+#[derive(Debug, PartialEq, Eq)]
+pub struct Frame {
+    pub payload: Vec<u8>,
+    pub compressed: bool,
+}
+
+/// Reads the next frame, returns `None` when the stream ended between frames.
+pub async fn read_frame<R: AsyncRead + Unpin>(
+    stream: &mut R,
+) -> Result<Option<Frame>, ClientError> {
+    let mut len_buf = [0u8; 4];
+    match stream.read_exact(&mut len_buf).await {
+        Ok(_) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+
+    let len = u32::from_be_bytes(len_buf);
+    let compressed = len & COMPRESSED_FLAG != 0;
+    let len = (len & !COMPRESSED_FLAG) as usize;
+    if len > MAX_MESSAGE_SIZE as usize {
+        return Err(ClientError::FrameTooLarge(len));
+    }
+
+    let mut payload = vec![0u8; len];
+    stream.read_exact(&mut payload).await?;
+    Ok(Some(Frame {
+        payload,
+        compressed,
+    }))
+}
+
+/// Writes an uncompressed frame and flushes the stream
+pub async fn write_frame<W: AsyncWrite + Unpin>(
+    stream: &mut W,
+    payload: &[u8],
+) -> Result<(), ClientError> {
+    let len = u32::try_from(payload.len())
+        .ok()
+        .filter(|len| *len <= MAX_MESSAGE_SIZE)
+        .ok_or(ClientError::FrameTooLarge(payload.len()))?;
+
+    let mut buf = Vec::with_capacity(payload.len() + 4);
+    buf.extend_from_slice(&len.to_be_bytes());
+    buf.extend_from_slice(payload);
+    stream.write_all(&buf).await?;
+    stream.flush().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn round_trip() -> Result<(), ClientError> {
+        let mut buf = Vec::new();
+        write_frame(&mut buf, b"first").await?;
+        write_frame(&mut buf, b"").await?;
+
+        let mut stream = &buf[..];
+        let frame = read_frame(&mut stream).await?;
+        assert_eq!(frame.map(|frame| frame.payload), Some(b"first".to_vec()));
+        let frame = read_frame(&mut stream).await?;
+        assert_eq!(frame.map(|frame| frame.payload), Some(Vec::new()));
+        assert_eq!(read_frame(&mut stream).await?, None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn compressed_and_oversized() -> Result<(), ClientError> {
+        let mut stream = &(COMPRESSED_FLAG | 2)
+            .to_be_bytes()
+            .into_iter()
+            .chain([1, 2])
+            .collect::<Vec<_>>()[..];
+        let frame = read_frame(&mut stream).await?;
+        assert_eq!(
+            frame,
+            Some(Frame {
+                payload: vec![1, 2],
+                compressed: true
+            })
+        );
+
+        let mut stream = &(MAX_MESSAGE_SIZE + 1).to_be_bytes()[..];
+        assert!(matches!(
+            read_frame(&mut stream).await,
+            Err(ClientError::FrameTooLarge(_))
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # Client
+//! SDK for writing clients and bots in Rust. Handles connecting to a server,
+//! the framing, the join flow and (de)compression, so you only deal with the
+//! types from the `protocol` crate.
+//!
+//! ```no_run
+//! use client::ClientBuilder;
+//!
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut client = ClientBuilder::new()
+//!     .with_certificates_from_file("certs.pem")?
+//!     .connect("127.0.0.1:42069".parse()?, "localhost")
+//!     .await?;
+//!
+//! let accept = client.join(42, 0).await?;
+//! println!("joined as {}", accept.uuid);
+//!
+//! while let Some(event) = client.recv().await? {
+//!     println!("{event:?}");
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+#![expect(clippy::multiple_crate_versions)]
+
+mod builder;
+mod client;
+mod commands;
+mod error;
+mod events;
+mod frame;
+
+pub use builder::ClientBuilder;
+pub use client::Client;
+pub use commands::CommandSink;
+pub use error::ClientError;
+pub use events::EventStream;
+pub use wire::{Codec, compression::Dictionary};
@@ -7,28 +7,50 @@
 
 pub mod logging;
 pub mod network;
+pub mod telemetry;
+pub mod tick;
 
-use crate::config::{logging::LoggingConfig, network::NetworkConfig};
+use crate::config::{
+    logging::LoggingConfig, network::NetworkConfig, telemetry::TelemetryConfig, tick::TickConfig,
+};
 use bevy::ecs::resource::Resource;
 
+/// Longest `motd` in bytes, so the status of the server fits in a frame
+pub const MAX_MOTD_LENGTH: usize = 1024;
+
 /// The main `Config` struct used to configure the server.
-#[derive(Debug, Resource, serde::Deserialize, konfik::Config)]
+#[derive(
+    Debug, Resource, serde::Deserialize, serde::Serialize, schemars::JsonSchema, konfik::Config,
+)]
 pub struct Config {
     /// Maximum amount of players on the server
     pub max_players: u32,
+    /// Message of the day, shown in the status of the server, at most
+    /// 1024 bytes
+    #[serde(default)]
+    pub motd: String,
     /// Network settings
     pub network: NetworkConfig,
+    /// Tick rate of the simulation
+    #[serde(default)]
+    pub tick: TickConfig,
     /// Logging config
     #[serde(default)]
     pub logging: LoggingConfig,
+    /// Metrics and health endpoints
+    #[serde(default)]
+    pub telemetry: TelemetryConfig,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             max_players: 100,
+            motd: String::new(),
             network: NetworkConfig::default(),
+            tick: TickConfig::default(),
             logging: LoggingConfig::default(),
+            telemetry: TelemetryConfig::default(),
         }
     }
 }
@@ -4,8 +4,13 @@
 //! # `Logging`
 //! Defines the Config used for logging.
 
+use crate::validate::Issues;
+use std::path::PathBuf;
+
 /// The config used for setting up logging
-#[derive(Debug, Clone, serde::Deserialize, Default)]
+#[derive(
+    Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema, Default,
+)]
 pub struct LoggingConfig {
     /// The output formatting
     #[serde(default)]
@@ -13,12 +18,139 @@ pub struct LoggingConfig {
     /// The maximum log level for the output
     #[serde(default)]
     pub log_level: LogLevel,
+    /// Directives overriding `log_level` for specific modules, as a comma
+    /// separated list of `target=level`
+    #[serde(default)]
+    #[schemars(example = "network=debug,quinn=warn")]
+    pub filter: Option<String>,
+    /// Files the logs are written to next to the console
+    #[serde(default)]
+    pub files: Vec<LogFileConfig>,
+    /// Collector the spans are exported to, nothing is exported when this
+    /// isn't set
+    #[serde(default)]
+    #[schemars(example = OtlpConfig::default())]
+    pub otlp: Option<OtlpConfig>,
+}
+
+impl LoggingConfig {
+    /// Checks the values that can change while the server runs
+    pub(crate) fn validate_reloadable(&self, issues: &mut Issues) {
+        if let Some(filter) = &self.filter
+            && let Err(e) = tracing_subscriber::EnvFilter::builder().parse(filter)
+        {
+            issues.push("logging.filter", format!("invalid directive: {e}"));
+        }
+    }
+
+    pub(crate) fn validate(&self, issues: &mut Issues) {
+        for (index, file) in self.files.iter().enumerate() {
+            file.validate(index, issues);
+            // outputs of the same name would rotate and delete each other's files
+            if self.files[..index]
+                .iter()
+                .any(|other| other.directory == file.directory && other.name == file.name)
+            {
+                issues.push(
+                    &format!("logging.files.{index}.name"),
+                    format!(
+                        "{} is already written to {} by another output",
+                        file.name,
+                        file.directory.display()
+                    ),
+                );
+            }
+        }
+        if let Some(otlp) = &self.otlp {
+            otlp.validate(issues);
+        }
+    }
+}
+
+/// A file output, with its own format, that is rotated and cleaned up
+#[derive(
+    Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
+)]
+#[serde(default)]
+pub struct LogFileConfig {
+    /// Directory the files are written to, created when it doesn't exist
+    pub directory: PathBuf,
+    /// Name of the files, `server` writes to `server.log` with size-based
+    /// rotation and to `server.2025-06-01.log` with daily rotation
+    pub name: String,
+    /// The output formatting
+    pub format: OutputFormat,
+    /// When a new file is started
+    pub rotation: Rotation,
+    /// Size in bytes after which a new file is started, with `Size` rotation
+    pub max_size: u64,
+    /// Amount of rotated files that are kept, older ones are deleted
+    pub retention: usize,
+}
+
+impl Default for LogFileConfig {
+    fn default() -> Self {
+        Self {
+            directory: "logs".into(),
+            name: "server".to_owned(),
+            format: OutputFormat::Json,
+            rotation: Rotation::default(),
+            max_size: 10 * 1024 * 1024,
+            retention: 7,
+        }
+    }
+}
+
+impl LogFileConfig {
+    fn validate(&self, index: usize, issues: &mut Issues) {
+        if self.name.is_empty() || self.name.contains(['/', '\\']) {
+            issues.push(
+                &format!("logging.files.{index}.name"),
+                "must be a file name without a directory",
+            );
+        }
+        if self.directory.exists() && !self.directory.is_dir() {
+            issues.push(
+                &format!("logging.files.{index}.directory"),
+                format!("{} isn't a directory", self.directory.display()),
+            );
+        }
+        if self.rotation == Rotation::Size && self.max_size == 0 {
+            issues.push(
+                &format!("logging.files.{index}.max_size"),
+                "must be at least 1",
+            );
+        }
+    }
+}
+
+/// When a log file is rotated
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    serde::Deserialize,
+    serde::Serialize,
+    schemars::JsonSchema,
+    Default,
+)]
+pub enum Rotation {
+    /// A new file every day, at midnight UTC
+    #[default]
+    Daily,
+    /// A new file once the current one reaches `max_size`
+    Size,
 }
 
 /// The formatting of the output.
-#[derive(Debug, Clone, serde::Deserialize)]
+#[derive(
+    Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, schemars::JsonSchema, Default,
+)]
 pub enum OutputFormat {
     /// Default
+    #[default]
     Default,
     /// Human-readable
     Pretty,
@@ -26,20 +158,17 @@ pub enum OutputFormat {
     Json,
 }
 
-impl Default for OutputFormat {
-    fn default() -> Self {
-        Self::Default
-    }
-}
-
 /// The log level
-#[derive(Debug, Clone, serde::Deserialize)]
+#[derive(
+    Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, schemars::JsonSchema, Default,
+)]
 pub enum LogLevel {
     /// Lowest level, very verbose
     Trace,
     /// Lower priority information
     Debug,
     /// Useful information
+    #[default]
     Info,
     /// Hazardous information
     Warn,
@@ -47,8 +176,47 @@ pub enum LogLevel {
     Error,
 }
 
-impl Default for LogLevel {
+/// Export of spans to an OpenTelemetry collector over OTLP/HTTP
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
+#[serde(default)]
+pub struct OtlpConfig {
+    /// Base URL of the collector, spans are posted to `/v1/traces` below it.
+    /// `https` URLs are exported over TLS.
+    pub endpoint: String,
+    /// Reported as the `service.name` of the spans
+    pub service_name: String,
+    /// Share of the traces that is exported, from 0 to 1
+    pub sampling_ratio: f64,
+}
+
+impl Default for OtlpConfig {
     fn default() -> Self {
-        Self::Info
+        Self {
+            endpoint: "http://127.0.0.1:4318".to_owned(),
+            service_name: "crypts-of-the-lost".to_owned(),
+            sampling_ratio: 1.,
+        }
+    }
+}
+
+impl OtlpConfig {
+    fn validate(&self, issues: &mut Issues) {
+        if !self
+            .endpoint
+            .strip_prefix("http://")
+            .or_else(|| self.endpoint.strip_prefix("https://"))
+            .is_some_and(|rest| !rest.is_empty() && !rest.starts_with('/'))
+        {
+            issues.push(
+                "logging.otlp.endpoint",
+                "must be an http:// or https:// URL with a host",
+            );
+        }
+        if !(0. ..=1.).contains(&self.sampling_ratio) {
+            issues.push(
+                "logging.otlp.sampling_ratio",
+                format!("must be between 0 and 1, got {}", self.sampling_ratio),
+            );
+        }
     }
 }
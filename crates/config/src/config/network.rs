@@ -4,10 +4,13 @@
 //! `NetworkConfig`
 //! `NetworkConfig` struct for settings used by the network systems.
 
+use crate::validate::Issues;
 use std::{net::SocketAddr, path::PathBuf};
 
 /// `NetworkConfig` struct for setting concerning the network systems
-#[derive(Debug, Clone, serde::Deserialize)]
+#[derive(
+    Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
+)]
 pub struct NetworkConfig {
     /// Socket to bind the server to
     pub socket: SocketAddr,
@@ -15,6 +18,18 @@ pub struct NetworkConfig {
     pub certs: PathBuf,
     /// Path to the TLS private key (self- or externally-signed)
     pub key: PathBuf,
+    /// Compression of outbound frames
+    #[serde(default)]
+    pub compression: CompressionConfig,
+    /// Socket to accept WebSocket connections over TLS on, for browser and
+    /// scripting clients. Requires the server to be built with the `websocket` feature.
+    #[serde(default)]
+    #[schemars(example = "0.0.0.0:443")]
+    pub websocket: Option<SocketAddr>,
+    /// Path of a capture file to record every session to, for replaying them later
+    #[serde(default)]
+    #[schemars(example = "session.cap")]
+    pub record: Option<PathBuf>,
 }
 
 impl Default for NetworkConfig {
@@ -24,6 +39,56 @@ impl Default for NetworkConfig {
             socket: "0.0.0.0:1234".parse().unwrap(),
             certs: "certs.pem".parse().unwrap(),
             key: "key.pem".parse().unwrap(),
+            compression: CompressionConfig::default(),
+            websocket: None,
+            record: None,
+        }
+    }
+}
+
+impl NetworkConfig {
+    pub(crate) fn validate(&self, issues: &mut Issues) {
+        issues.bind_udp("network.socket", self.socket);
+        issues.file("network.certs", &self.certs);
+        issues.file("network.key", &self.key);
+        if let Some(dictionary) = &self.compression.dictionary {
+            issues.file("network.compression.dictionary", dictionary);
+        }
+        if let Some(websocket) = self.websocket {
+            issues.bind_tcp("network.websocket", websocket);
+        }
+        if let Some(record) = &self.record {
+            issues.parent_dir("network.record", record);
+        }
+    }
+}
+
+/// Settings for the compression of frames sent to clients.
+///
+/// Compression is only used when the client asks for it while joining.
+#[derive(
+    Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
+)]
+#[serde(default)]
+pub struct CompressionConfig {
+    /// Whether clients are allowed to negotiate compression
+    pub enabled: bool,
+    /// Frames smaller than this many bytes are sent uncompressed
+    pub threshold: usize,
+    /// Zstd compression level, ignored by LZ4
+    pub level: i32,
+    /// Path to a dictionary shared with clients, trained on common payloads
+    #[schemars(example = "dictionary.zstd")]
+    pub dictionary: Option<PathBuf>,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold: 1024,
+            level: 3,
+            dictionary: None,
         }
     }
 }
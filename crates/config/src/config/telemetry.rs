@@ -0,0 +1,47 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # `Telemetry`
+//! Defines the Config used for the endpoints that monitor the server.
+
+use crate::validate::Issues;
+use std::net::SocketAddr;
+
+/// The config of the endpoints operators use to monitor the server
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
+#[serde(default)]
+pub struct TelemetryConfig {
+    /// Local address to serve Prometheus metrics on, at `/metrics`.
+    /// Metrics aren't served when this isn't set.
+    #[schemars(example = "127.0.0.1:9100")]
+    pub metrics: Option<SocketAddr>,
+    /// Local address to serve the health checks on, at `/health` and `/ready`.
+    /// May be the same address as `metrics`.
+    #[schemars(example = "127.0.0.1:9100")]
+    pub health: Option<SocketAddr>,
+    /// Seconds without a finished tick after which the server is unhealthy
+    pub stall_timeout: f64,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            metrics: None,
+            health: None,
+            stall_timeout: 5.,
+        }
+    }
+}
+
+impl TelemetryConfig {
+    pub(crate) fn validate(&self, issues: &mut Issues) {
+        issues.positive("telemetry.stall_timeout", self.stall_timeout);
+        if let Some(metrics) = self.metrics {
+            issues.bind_tcp("telemetry.metrics", metrics);
+        }
+        // both are served by the same listener when they're equal
+        if let Some(health) = self.health.filter(|health| self.metrics != Some(*health)) {
+            issues.bind_tcp("telemetry.health", health);
+        }
+    }
+}
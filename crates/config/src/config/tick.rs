@@ -0,0 +1,47 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # `Tick`
+//! Defines the Config used for the fixed timestep of the simulation.
+
+use crate::validate::Issues;
+use std::time::Duration;
+
+/// The config of the ticks the simulation advances in
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
+#[serde(default)]
+pub struct TickConfig {
+    /// Ticks per second
+    pub rate: f64,
+    /// Most ticks run back to back to catch up after a slow one. Time the
+    /// server falls behind beyond that is dropped, slowing the simulation down.
+    pub max_catch_up: u32,
+}
+
+impl Default for TickConfig {
+    fn default() -> Self {
+        Self {
+            rate: 16.,
+            max_catch_up: 4,
+        }
+    }
+}
+
+impl TickConfig {
+    /// Duration of a single tick, one over the rate. Falls back to the
+    /// default rate when the rate isn't a positive number.
+    #[must_use]
+    pub fn timestep(&self) -> Duration {
+        Duration::try_from_secs_f64(self.rate.recip())
+            .ok()
+            .filter(|timestep| !timestep.is_zero())
+            .unwrap_or_else(|| Duration::from_secs_f64(Self::default().rate.recip()))
+    }
+
+    pub(crate) fn validate(&self, issues: &mut Issues) {
+        issues.positive("tick.rate", self.rate);
+        if self.max_catch_up == 0 {
+            issues.push("tick.max_catch_up", "must be at least 1");
+        }
+    }
+}
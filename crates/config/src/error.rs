@@ -0,0 +1,50 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # Error
+//! Defines the error type for the config crate
+
+use crate::validate::Issue;
+use std::{fmt::Write, path::PathBuf};
+use thiserror::Error;
+
+/// Error type used by [`crate::parse_config`]
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    /// A config file couldn't be read
+    #[error("Wasn't able to read {}: {source}", path.display())]
+    Read {
+        /// The file
+        path: PathBuf,
+        /// Error from IO
+        source: std::io::Error,
+    },
+    /// A config file isn't valid TOML
+    #[error("{} isn't valid TOML: {source}", path.display())]
+    Toml {
+        /// The file
+        path: PathBuf,
+        /// Error from the TOML parser
+        source: Box<toml::de::Error>,
+    },
+    /// The `include` key of a file isn't a path or a list of paths
+    #[error("include in {} must be a path or a list of paths", .0.display())]
+    InvalidInclude(PathBuf),
+    /// A file includes itself, directly or through other files
+    #[error("{} includes itself", .0.display())]
+    IncludeCycle(PathBuf),
+    /// The merged layers don't match the `Config` struct
+    #[error("Wasn't able to load the config: {0}")]
+    Deserialize(#[from] serde_json::Error),
+    /// The config was loaded, but has values the server can't start with
+    #[error("{}", list_issues(.0))]
+    Invalid(Vec<Issue>),
+}
+
+fn list_issues(issues: &[Issue]) -> String {
+    let mut out = format!("The config has {} problem(s):", issues.len());
+    for issue in issues {
+        let _ = write!(out, "\n  - {issue}");
+    }
+    out
+}
@@ -3,12 +3,22 @@
 
 //! # Config
 //! Defines the config struct used for the game server. Will be read from
-//! a TOML file and the CLI can override those settings.
+//! TOML files, environment variables and the CLI can override those settings.
 
 #![expect(clippy::multiple_crate_versions)]
 
 pub mod config;
 pub use config::Config;
 
+mod error;
+pub use error::ConfigError;
+
 mod parse;
-pub use parse::parse_config;
+pub use parse::{ConfigOptions, load_config, parse_config};
+
+pub mod reload;
+pub use reload::{ConfigChanged, ConfigReload};
+
+pub mod schema;
+pub mod source;
+pub mod validate;
@@ -3,21 +3,78 @@
 
 //! # Parse
 
-use crate::Config;
-use konfik::{ConfigLoader, Error};
+use crate::{Config, ConfigError, source::Sources};
+use std::{collections::BTreeMap, path::PathBuf};
 
-/// Parses the `Config` struct.
+/// Prefix of the environment variables overriding the config
+const ENV_PREFIX: &str = "COTL";
+
+/// File read when no config file is given, skipped when it doesn't exist
+const DEFAULT_FILE: &str = "config.toml";
+
+/// Which files the config is read from, and the values the command line
+/// overrides
+#[derive(Debug, Clone, Default)]
+pub struct ConfigOptions {
+    /// The base config file, `config.toml` in the working directory when not set
+    pub path: Option<PathBuf>,
+    /// Profile merged on top of the base file, `dev` reads `config.dev.toml`
+    /// next to `config.toml`
+    pub profile: Option<String>,
+    /// Top-level fields set by command line arguments, with their raw value
+    pub overrides: BTreeMap<String, String>,
+}
+
+impl ConfigOptions {
+    /// The files to merge, from the lowest to the highest priority
+    #[must_use]
+    pub fn files(&self) -> Vec<PathBuf> {
+        let base = self.path.clone().unwrap_or_else(|| DEFAULT_FILE.into());
+        let profile = self.profile.as_ref().map(|profile| {
+            let stem = base.file_stem().unwrap_or_default().to_string_lossy();
+            let mut name = format!("{stem}.{profile}");
+            if let Some(extension) = base.extension() {
+                name = format!("{name}.{}", extension.to_string_lossy());
+            }
+            base.with_file_name(name)
+        });
+
+        // only an explicitly given base file has to exist
+        let base = (self.path.is_some() || base.exists()).then_some(base);
+        base.into_iter().chain(profile).collect()
+    }
+}
+
+/// Parses and validates the `Config` struct.
+///
+/// Parses the config struct by merging the base file, the files it
+/// includes and the profile, then the `COTL_` environment variables and the
+/// cli args on top of it.
+///
+/// # Errors
 ///
-/// Parses the config struct by using the default values as the base
-/// and then merging the TOML file and the cli args on top of it.
+/// Returns an error when a file couldn't be read, deserialization to the
+/// `Config` struct failed, or with every issue found when validating it.
+pub fn parse_config(options: &ConfigOptions) -> Result<Config, ConfigError> {
+    let (config, sources) = load_config(options)?;
+    let issues = config.validate(&sources);
+    if issues.is_empty() {
+        Ok(config)
+    } else {
+        Err(ConfigError::Invalid(issues))
+    }
+}
+
+/// Merges the layers into the `Config` struct like [`parse_config`],
+/// without validating it. Also returns the layers, to look up where a value
+/// was set.
 ///
 /// # Errors
 ///
-/// Returns an error when deserialization to the `Config` struct failed.
-pub fn parse_config() -> Result<Config, Error> {
-    ConfigLoader::default()
-        .with_config_file("config.toml")
-        .with_env_prefix("COTL")
-        .with_cli()
-        .load()
+/// Returns an error when a file couldn't be read or deserialization to the
+/// `Config` struct failed.
+pub fn load_config(options: &ConfigOptions) -> Result<(Config, Sources), ConfigError> {
+    let sources = Sources::load(&options.files(), ENV_PREFIX, &options.overrides)?;
+    let config = serde_json::from_value(sources.merged())?;
+    Ok((config, sources))
 }
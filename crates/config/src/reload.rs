@@ -0,0 +1,222 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # Reload
+//! Watches the config files and applies the sections that can change while
+//! the server runs. Everything else needs a restart, changes to it are
+//! rejected with a warning.
+
+use crate::{Config, ConfigOptions, parse::load_config, source::Sources, validate::Issues};
+use bevy::{
+    app::{App, First, Plugin},
+    ecs::{
+        event::{Event, EventWriter},
+        resource::Resource,
+        system::ResMut,
+    },
+};
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+use tracing::{info, warn};
+
+/// How often the files are checked for changes
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Plugin reloading the config when one of its files changes.
+///
+/// Changed values are written into the [`Config`] resource, followed by a
+/// [`ConfigChanged`] event listing them.
+#[derive(Debug)]
+pub struct ConfigReload {
+    options: ConfigOptions,
+}
+
+impl ConfigReload {
+    /// Watches the files the config was read from with the given options
+    #[must_use]
+    pub const fn new(options: ConfigOptions) -> Self {
+        Self { options }
+    }
+}
+
+impl Plugin for ConfigReload {
+    fn build(&self, app: &mut App) {
+        let sources = load_config(&self.options)
+            .map(|(_, sources)| sources)
+            .unwrap_or_default();
+        let files = watched_files(&self.options, &sources);
+        app.add_event::<ConfigChanged>()
+            .insert_resource(Watch {
+                options: self.options.clone(),
+                files,
+                checked: Instant::now(),
+            })
+            .add_systems(First, watch_config);
+    }
+}
+
+/// Sent after values of the [`Config`] resource were reloaded
+#[derive(Debug, Clone, PartialEq, Eq, Event)]
+pub struct ConfigChanged {
+    /// Dotted paths of the changed values, such as `motd`
+    pub changed: Vec<&'static str>,
+}
+
+/// Outcome of reloading the config
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Reload {
+    /// Values that were changed
+    pub applied: Vec<&'static str>,
+    /// Values that differ, but only take effect after a restart
+    pub rejected: Vec<&'static str>,
+}
+
+impl Config {
+    /// Takes the reloadable values of a newly loaded config: `max_players`,
+    /// `motd`, `logging.log_level` and `logging.filter`. Differences in other
+    /// values are returned as rejected and left as they are.
+    pub fn reload(&mut self, new: Self) -> Reload {
+        let mut reload = Reload::default();
+        let mut rejected = |path, changed: bool| {
+            if changed {
+                reload.rejected.push(path);
+            }
+        };
+        let (network, old) = (&new.network, &self.network);
+        rejected("network.socket", network.socket != old.socket);
+        rejected("network.certs", network.certs != old.certs);
+        rejected("network.key", network.key != old.key);
+        rejected(
+            "network.compression",
+            network.compression != old.compression,
+        );
+        rejected("network.websocket", network.websocket != old.websocket);
+        rejected("network.record", network.record != old.record);
+        rejected("tick", new.tick != self.tick);
+        rejected("telemetry", new.telemetry != self.telemetry);
+        rejected(
+            "logging.output_format",
+            new.logging.output_format != self.logging.output_format,
+        );
+        rejected("logging.files", new.logging.files != self.logging.files);
+        rejected("logging.otlp", new.logging.otlp != self.logging.otlp);
+
+        if new.max_players != self.max_players {
+            self.max_players = new.max_players;
+            reload.applied.push("max_players");
+        }
+        if new.motd != self.motd {
+            self.motd = new.motd;
+            reload.applied.push("motd");
+        }
+        if new.logging.log_level != self.logging.log_level {
+            self.logging.log_level = new.logging.log_level;
+            reload.applied.push("logging.log_level");
+        }
+        if new.logging.filter != self.logging.filter {
+            self.logging.filter = new.logging.filter;
+            reload.applied.push("logging.filter");
+        }
+        reload
+    }
+}
+
+/// The files of the config and when they were last modified
+#[derive(Debug, Resource)]
+struct Watch {
+    options: ConfigOptions,
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+    checked: Instant,
+}
+
+/// The base file, the profile and every include, which may not exist yet
+fn watched_files(options: &ConfigOptions, sources: &Sources) -> Vec<(PathBuf, Option<SystemTime>)> {
+    let mut files = options.files();
+    files.extend(sources.files().map(Path::to_path_buf));
+    files.sort();
+    files.dedup();
+    files
+        .into_iter()
+        .map(|path| {
+            let modified = modified(&path);
+            (path, modified)
+        })
+        .collect()
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+fn watch_config(
+    mut watch: ResMut<Watch>,
+    mut config: ResMut<Config>,
+    mut events: EventWriter<ConfigChanged>,
+) {
+    if watch.checked.elapsed() < POLL_INTERVAL {
+        return;
+    }
+    watch.checked = Instant::now();
+    if watch
+        .files
+        .iter()
+        .all(|(path, modified)| self::modified(path) == *modified)
+    {
+        return;
+    }
+
+    let (new, sources) = match load_config(&watch.options) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            warn!("Not reloading the config: {e}");
+            return;
+        }
+    };
+    watch.files = watched_files(&watch.options, &sources);
+
+    let mut issues = Issues::new(&sources);
+    new.validate_reloadable(&mut issues);
+    let issues = issues.into_inner();
+    if !issues.is_empty() {
+        for issue in issues {
+            warn!("Not reloading the config: {issue}");
+        }
+        return;
+    }
+
+    let reload = config.reload(new);
+    for path in &reload.rejected {
+        warn!("{path} changed, restart the server to apply it");
+    }
+    if !reload.applied.is_empty() {
+        info!("reloaded {}", reload.applied.join(", "));
+        events.write(ConfigChanged {
+            changed: reload.applied,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_reloadable_values_are_applied() {
+        let mut config = Config::default();
+        let mut new = Config {
+            motd: "welcome".to_owned(),
+            ..Config::default()
+        };
+        new.network.socket = ([127, 0, 0, 1], 4000).into();
+
+        let reload = config.reload(new);
+        assert_eq!(reload.applied, ["motd"]);
+        assert_eq!(reload.rejected, ["network.socket"]);
+        assert_eq!(config.motd, "welcome");
+        assert_eq!(config.network.socket, Config::default().network.socket);
+    }
+}
@@ -0,0 +1,165 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # Schema
+//! Describes the config for operators and their editors: as a JSON Schema
+//! built from the config types and their doc comments, and as a commented
+//! `config.toml` holding every default value.
+
+use crate::Config;
+use schemars::generate::SchemaSettings;
+use serde_json::{Value, json};
+use std::fmt::Write;
+
+/// Creates the JSON Schema of the config file
+#[must_use]
+pub fn config_schema() -> Value {
+    let mut schema = SchemaSettings::draft2020_12()
+        .into_generator()
+        .into_root_schema_for::<Config>()
+        .to_value();
+    if let Some(properties) = schema.get_mut("properties").and_then(Value::as_object_mut) {
+        properties.insert(
+            "include".to_owned(),
+            json!({
+                "description": "Files merged below this one, relative to it",
+                "anyOf": [
+                    { "type": "string" },
+                    { "type": "array", "items": { "type": "string" } },
+                ],
+            }),
+        );
+    }
+    schema
+}
+
+/// [`config_schema`] as pretty printed JSON, ending with a newline
+///
+/// # Errors
+/// Returns a `serde_json::Error` when the schema can't be serialized.
+pub fn config_schema_string() -> Result<String, serde_json::Error> {
+    let mut schema = serde_json::to_string_pretty(&config_schema())?;
+    schema.push('\n');
+    Ok(schema)
+}
+
+/// Writes a `config.toml` with every default value, each commented with its
+/// description. Values without a default are commented out with an example.
+///
+/// # Errors
+/// Returns a `serde_json::Error` when the defaults can't be serialized.
+pub fn default_config() -> Result<String, serde_json::Error> {
+    let schema = config_schema();
+    let defaults = serde_json::to_value(Config::default())?;
+
+    let mut out = String::from(
+        "# Config of the Crypts of the Lost server, every value is the default.\n\
+         # Generated by `server config init`, see `server config schema` for the JSON Schema.\n",
+    );
+    write_table(&mut out, &schema, &schema, &defaults, "");
+    Ok(out)
+}
+
+/// Writes the values of a table, then its sub tables
+fn write_table(out: &mut String, root: &Value, schema: &Value, value: &Value, path: &str) {
+    let schema = resolve(root, schema);
+    let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+        return;
+    };
+    let values = value.as_object().cloned().unwrap_or_default();
+
+    let mut tables = Vec::new();
+    for (key, property) in properties {
+        match values.get(key) {
+            Some(Value::Object(_)) => tables.push((key, property)),
+            Some(value) if !value.is_null() => {
+                comment(out, root, property);
+                let _ = writeln!(out, "{key} = {}", render(value));
+            }
+            _ => {
+                let Some(example) = resolve(root, property)
+                    .get("examples")
+                    .or_else(|| property.get("examples"))
+                    .and_then(|examples| examples.get(0))
+                else {
+                    continue;
+                };
+                comment(out, root, property);
+                let _ = writeln!(out, "# {key} = {}", render(example));
+            }
+        }
+    }
+
+    for (key, property) in tables {
+        let path = if path.is_empty() {
+            key.clone()
+        } else {
+            format!("{path}.{key}")
+        };
+        out.push('\n');
+        comment(out, root, property);
+        let _ = writeln!(out, "[{path}]");
+        write_table(out, root, property, &values[key.as_str()], &path);
+    }
+}
+
+/// Writes the description of a value and the values it can take
+fn comment(out: &mut String, root: &Value, property: &Value) {
+    let description = property
+        .get("description")
+        .or_else(|| resolve(root, property).get("description"))
+        .and_then(Value::as_str);
+    for line in description.into_iter().flat_map(str::lines) {
+        let _ = writeln!(out, "{}", format!("# {line}").trim_end());
+    }
+
+    let variants: Vec<&str> = resolve(root, property)
+        .get("oneOf")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|variant| variant.get("const").and_then(Value::as_str))
+        .collect();
+    if !variants.is_empty() {
+        let _ = writeln!(out, "# One of: {}", variants.join(", "));
+    }
+}
+
+/// Follows a `$ref` to the definitions of the root
+pub(crate) fn resolve<'a>(root: &'a Value, schema: &'a Value) -> &'a Value {
+    schema
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|reference| reference.strip_prefix("#/$defs/"))
+        .and_then(|name| root.get("$defs")?.get(name))
+        .unwrap_or(schema)
+}
+
+/// Renders a value the way TOML writes it
+fn render(value: &Value) -> String {
+    toml::Value::try_from(value).map_or_else(|_| value.to_string(), |value| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// TOML has no null, unset options are left out
+    fn strip_nulls(value: &mut Value) {
+        if let Value::Object(table) = value {
+            table.retain(|_, value| !value.is_null());
+            table.values_mut().for_each(strip_nulls);
+        }
+    }
+
+    #[test]
+    fn default_config_parses_to_the_defaults() -> Result<(), Box<dyn std::error::Error>> {
+        let text = default_config()?;
+        let parsed: Value = toml::from_str::<toml::Value>(&text)?.try_into()?;
+        let mut defaults = serde_json::to_value(Config::default())?;
+        strip_nulls(&mut defaults);
+        assert_eq!(parsed, defaults);
+        assert!(text.contains("# websocket = \"0.0.0.0:443\""));
+        Ok(())
+    }
+}
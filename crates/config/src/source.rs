@@ -0,0 +1,385 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # Source
+//! Reads the layers the config is merged from, and tracks which layer set a
+//! value, so problems can point the operator to the file, environment
+//! variable or CLI argument to fix.
+
+use crate::{
+    Config, ConfigError,
+    schema::{config_schema, resolve},
+};
+use konfik::config_meta::ConfigMetadata;
+use serde_json::{Map, Value};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+/// Key listing the files a config file includes
+const INCLUDE: &str = "include";
+
+/// The layer a value of the config was set in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// A config file
+    File(PathBuf),
+    /// An environment variable, with the `COTL_` prefix
+    Env(String),
+    /// A command line argument
+    Cli(String),
+    /// Not set anywhere, the default value is used
+    Default,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "set in {}", path.display()),
+            Self::Env(name) => write!(f, "set by the environment variable {name}"),
+            Self::Cli(arg) => write!(f, "set by the argument {arg}"),
+            Self::Default => f.write_str("default value"),
+        }
+    }
+}
+
+/// The values every layer set, from the lowest to the highest priority
+#[derive(Debug, Default)]
+pub struct Sources {
+    files: Vec<(PathBuf, Value)>,
+    env: BTreeMap<String, (String, Value)>,
+    cli: BTreeMap<String, (String, Value)>,
+}
+
+impl Sources {
+    /// Reads the layers in the order they are merged: the files with the
+    /// files they include, then the environment variables with the given
+    /// prefix, then the overrides of the command line
+    ///
+    /// # Errors
+    /// Returns an error when a file or one of its includes can't be read or
+    /// isn't valid TOML, or when files include each other.
+    pub fn load(
+        files: &[PathBuf],
+        env_prefix: &str,
+        overrides: &BTreeMap<String, String>,
+    ) -> Result<Self, ConfigError> {
+        let mut sources = Self::default();
+        for path in files {
+            sources.read_file(path, &mut Vec::new())?;
+        }
+
+        let schema = config_schema();
+        let field_schema = |field: &str| &schema["properties"][field];
+        sources.env = Config::config_metadata()
+            .fields
+            .iter()
+            .filter_map(|field| {
+                let name = format!("{env_prefix}_{}", field.name.to_uppercase());
+                let value = std::env::var(&name).ok()?;
+                let value = parse_value(&value, &schema, field_schema(&field.name));
+                Some((field.name.clone(), (name, value)))
+            })
+            .collect();
+
+        sources.cli = overrides
+            .iter()
+            .map(|(field, value)| {
+                let value = parse_value(value, &schema, field_schema(field));
+                (field.clone(), (format!("--{field}"), value))
+            })
+            .collect();
+
+        Ok(sources)
+    }
+
+    /// Reads a file after the files it includes, so its own values win.
+    /// Includes are relative to the file including them.
+    fn read_file(&mut self, path: &Path, stack: &mut Vec<PathBuf>) -> Result<(), ConfigError> {
+        let canonical = path.canonicalize().map_err(|source| ConfigError::Read {
+            path: path.to_owned(),
+            source,
+        })?;
+        if stack.contains(&canonical) {
+            return Err(ConfigError::IncludeCycle(path.to_owned()));
+        }
+
+        let content = std::fs::read_to_string(path).map_err(|source| ConfigError::Read {
+            path: path.to_owned(),
+            source,
+        })?;
+        let value =
+            toml::from_str::<toml::Value>(&content).map_err(|source| ConfigError::Toml {
+                path: path.to_owned(),
+                source: Box::new(source),
+            })?;
+        let Value::Object(mut table) = serde_json::to_value(value)? else {
+            return Ok(());
+        };
+
+        let includes = match table.remove(INCLUDE) {
+            None => Vec::new(),
+            Some(Value::String(include)) => vec![include],
+            Some(Value::Array(includes)) => includes
+                .into_iter()
+                .map(|include| match include {
+                    Value::String(include) => Ok(include),
+                    _ => Err(ConfigError::InvalidInclude(path.to_owned())),
+                })
+                .collect::<Result<_, _>>()?,
+            Some(_) => return Err(ConfigError::InvalidInclude(path.to_owned())),
+        };
+
+        stack.push(canonical);
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        for include in includes {
+            self.read_file(&dir.join(include), stack)?;
+        }
+        stack.pop();
+
+        self.files.push((path.to_owned(), Value::Object(table)));
+        Ok(())
+    }
+
+    /// Every file that was read, includes and profiles included
+    pub fn files(&self) -> impl Iterator<Item = &Path> {
+        self.files.iter().map(|(path, _)| path.as_path())
+    }
+
+    /// Merges the layers, values of higher layers replace those of lower
+    /// ones and tables are merged key by key
+    #[must_use]
+    pub fn merged(&self) -> Value {
+        let files = self.files.iter().map(|(_, value)| value.clone());
+        let overrides = [&self.env, &self.cli].map(|layer| {
+            Value::Object(
+                layer
+                    .iter()
+                    .map(|(field, (_, value))| (field.clone(), value.clone()))
+                    .collect(),
+            )
+        });
+        files
+            .chain(overrides)
+            .fold(Value::Object(Map::new()), merge)
+    }
+
+    /// The layer with the highest priority that set the value at the dotted
+    /// path, such as `network.socket`
+    #[must_use]
+    pub fn locate(&self, path: &str) -> Source {
+        let keys: Vec<&str> = path.split('.').collect();
+        let Some((field, rest)) = keys.split_first() else {
+            return Source::Default;
+        };
+
+        if let Some((arg, value)) = self.cli.get(*field)
+            && contains(value, rest)
+        {
+            return Source::Cli(arg.clone());
+        }
+        if let Some((name, value)) = self.env.get(*field)
+            && contains(value, rest)
+        {
+            return Source::Env(name.clone());
+        }
+        self.files
+            .iter()
+            .rev()
+            .find(|(_, value)| contains(value, &keys))
+            .map_or(Source::Default, |(path, _)| Source::File(path.clone()))
+    }
+}
+
+/// Parses an environment variable or argument as the type of its field.
+///
+/// Text fields take the value as it is, so `COTL_MOTD=123` stays text.
+/// Other values are parsed as JSON, tables included, and the text fields in
+/// them also keep numbers and booleans as text.
+fn parse_value(value: &str, root: &Value, schema: &Value) -> Value {
+    if is_text(root, schema) {
+        return Value::String(value.to_owned());
+    }
+    let parsed = serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_owned()));
+    as_schema(parsed, root, schema)
+}
+
+/// Turns numbers and booleans into text where the schema expects text
+fn as_schema(value: Value, root: &Value, schema: &Value) -> Value {
+    match value {
+        Value::Object(table) => Value::Object(
+            table
+                .into_iter()
+                .map(|(key, value)| {
+                    let value = match property(root, schema, &key) {
+                        Some(schema) => as_schema(value, root, schema),
+                        None => value,
+                    };
+                    (key, value)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => {
+            let items_schema = variants(root, schema).find_map(|schema| schema.get("items"));
+            Value::Array(
+                items
+                    .into_iter()
+                    .map(|item| match items_schema {
+                        Some(schema) => as_schema(item, root, schema),
+                        None => item,
+                    })
+                    .collect(),
+            )
+        }
+        Value::Number(_) | Value::Bool(_) if is_text(root, schema) => {
+            Value::String(value.to_string())
+        }
+        value => value,
+    }
+}
+
+/// Whether the schema only accepts text, or nothing
+fn is_text(root: &Value, schema: &Value) -> bool {
+    let mut types = variants(root, schema).flat_map(|schema| match schema.get("type") {
+        Some(Value::String(name)) => vec![name.as_str()],
+        Some(Value::Array(names)) => names.iter().filter_map(Value::as_str).collect(),
+        _ => vec!["any"],
+    });
+    let mut text = false;
+    let only_text = types.all(|name| {
+        text |= name == "string";
+        matches!(name, "string" | "null")
+    });
+    text && only_text
+}
+
+/// The schema of a key of a table
+fn property<'a>(root: &'a Value, schema: &'a Value, key: &str) -> Option<&'a Value> {
+    variants(root, schema).find_map(|schema| schema.get("properties")?.get(key))
+}
+
+/// The schema itself, or the schemas it allows one of
+fn variants<'a>(root: &'a Value, schema: &'a Value) -> impl Iterator<Item = &'a Value> {
+    let schema = resolve(root, schema);
+    let alternatives: Vec<&Value> = ["anyOf", "oneOf"]
+        .into_iter()
+        .filter_map(|key| schema.get(key)?.as_array())
+        .flatten()
+        .map(|variant| resolve(root, variant))
+        .collect();
+    if alternatives.is_empty() {
+        vec![schema]
+    } else {
+        alternatives
+    }
+    .into_iter()
+}
+
+fn merge(base: Value, overlay: Value) -> Value {
+    match (base, overlay) {
+        (Value::Object(mut base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                let merged = match base.remove(&key) {
+                    Some(base) => merge(base, value),
+                    None => value,
+                };
+                base.insert(key, merged);
+            }
+            Value::Object(base)
+        }
+        (_, overlay) => overlay,
+    }
+}
+
+fn contains(value: &Value, keys: &[&str]) -> bool {
+    match keys.split_first() {
+        None => true,
+        // list items are addressed by their index, such as `logging.files.0`
+        Some((key, rest)) => match value {
+            Value::Array(items) => key.parse::<usize>().ok().and_then(|index| items.get(index)),
+            _ => value.get(key),
+        }
+        .is_some_and(|value| contains(value, rest)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn includes_are_merged_below_the_file() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        std::fs::create_dir(dir.path().join("conf.d"))?;
+        std::fs::write(
+            dir.path().join("config.toml"),
+            "include = [\"conf.d/network.toml\"]\nmax_players = 10\n[network]\nkey = \"base.pem\"\n",
+        )?;
+        std::fs::write(
+            dir.path().join("conf.d/network.toml"),
+            "max_players = 5\n[network]\nkey = \"included.pem\"\ncerts = \"certs.pem\"\n",
+        )?;
+        std::fs::write(dir.path().join("config.dev.toml"), "max_players = 20\n")?;
+
+        let files = [
+            dir.path().join("config.toml"),
+            dir.path().join("config.dev.toml"),
+        ];
+        let overrides =
+            BTreeMap::from([("network".to_owned(), r#"{"certs": "cli.pem"}"#.to_owned())]);
+        let sources = Sources::load(&files, "COTL_TEST", &overrides)?;
+        let merged = sources.merged();
+        assert_eq!(merged["max_players"], 20);
+        assert_eq!(merged["network"]["key"], "base.pem");
+        assert_eq!(merged["network"]["certs"], "cli.pem");
+        assert!(merged.get(INCLUDE).is_none());
+        assert_eq!(
+            sources.locate("network.certs"),
+            Source::Cli("--network".to_owned())
+        );
+        assert_eq!(
+            sources.locate("network.key"),
+            Source::File(dir.path().join("config.toml"))
+        );
+
+        std::fs::write(
+            dir.path().join("conf.d/network.toml"),
+            "include = \"../config.toml\"\n",
+        )?;
+        assert!(matches!(
+            Sources::load(&files, "COTL_TEST", &overrides),
+            Err(ConfigError::IncludeCycle(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn text_fields_keep_their_text() -> Result<(), ConfigError> {
+        let schema = config_schema();
+        for motd in ["123", "true", "null"] {
+            let value = parse_value(motd, &schema, &schema["properties"]["motd"]);
+            assert_eq!(value, Value::String(motd.to_owned()));
+        }
+
+        let overrides = BTreeMap::from([
+            ("max_players".to_owned(), "5".to_owned()),
+            ("motd".to_owned(), "42".to_owned()),
+            ("logging".to_owned(), r#"{"filter": 123}"#.to_owned()),
+        ]);
+        let merged = Sources::load(&[], "COTL_TEST", &overrides)?.merged();
+        assert_eq!(merged["max_players"], 5);
+        assert_eq!(merged["motd"], "42");
+        assert_eq!(merged["logging"]["filter"], "123");
+
+        let logging = parse_value(
+            r#"{"filter": true, "files": [{"name": 7}]}"#,
+            &schema,
+            &schema["properties"]["logging"],
+        );
+        assert_eq!(logging["filter"], "true");
+        assert_eq!(logging["files"][0]["name"], "7");
+        Ok(())
+    }
+}
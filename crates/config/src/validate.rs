@@ -0,0 +1,174 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # Validate
+//! Checks the loaded config for values the server can't start with, such as
+//! missing files or addresses that are already in use. Every problem is
+//! collected, so an operator can fix them all at once.
+
+use crate::{
+    Config,
+    config::MAX_MOTD_LENGTH,
+    source::{Source, Sources},
+};
+use std::{
+    fmt,
+    net::{SocketAddr, TcpListener, UdpSocket},
+    path::Path,
+};
+
+/// A value of the config the server can't start with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    /// Dotted path of the value, such as `network.socket`
+    pub path: String,
+    /// The layer that set the value
+    pub source: Source,
+    /// What is wrong with the value
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} ({})", self.path, self.message, self.source)
+    }
+}
+
+/// Collects the issues of every section
+#[derive(Debug)]
+pub(crate) struct Issues<'a> {
+    sources: &'a Sources,
+    issues: Vec<Issue>,
+}
+
+impl<'a> Issues<'a> {
+    pub(crate) const fn new(sources: &'a Sources) -> Self {
+        Self {
+            sources,
+            issues: Vec::new(),
+        }
+    }
+
+    pub(crate) fn into_inner(self) -> Vec<Issue> {
+        self.issues
+    }
+
+    /// Records an issue with the value at the path
+    pub(crate) fn push(&mut self, path: &str, message: impl Into<String>) {
+        self.issues.push(Issue {
+            path: path.to_owned(),
+            source: self.sources.locate(path),
+            message: message.into(),
+        });
+    }
+
+    /// Checks that the path points to an existing file
+    pub(crate) fn file(&mut self, path: &str, file: &Path) {
+        if !file.is_file() {
+            self.push(path, format!("the file {} doesn't exist", file.display()));
+        }
+    }
+
+    /// Checks that the directory a file will be created in exists
+    pub(crate) fn parent_dir(&mut self, path: &str, file: &Path) {
+        let dir = file
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."));
+        if !dir.is_dir() {
+            self.push(
+                path,
+                format!("the directory {} doesn't exist", dir.display()),
+            );
+        }
+    }
+
+    /// Checks that the value is a positive number
+    pub(crate) fn positive(&mut self, path: &str, value: f64) {
+        if !(value.is_finite() && value > 0.) {
+            self.push(path, format!("must be a positive number, got {value}"));
+        }
+    }
+
+    /// Checks that a UDP socket can be bound to the address
+    pub(crate) fn bind_udp(&mut self, path: &str, addr: SocketAddr) {
+        if let Err(e) = UdpSocket::bind(addr) {
+            self.push(path, format!("can't bind {addr}: {e}"));
+        }
+    }
+
+    /// Checks that a TCP listener can be bound to the address
+    pub(crate) fn bind_tcp(&mut self, path: &str, addr: SocketAddr) {
+        if let Err(e) = TcpListener::bind(addr) {
+            self.push(path, format!("can't bind {addr}: {e}"));
+        }
+    }
+}
+
+impl Config {
+    /// Checks every section of the config, the sources are used to tell
+    /// where a wrong value was set
+    #[must_use]
+    pub fn validate(&self, sources: &Sources) -> Vec<Issue> {
+        let mut issues = Issues::new(sources);
+        self.validate_reloadable(&mut issues);
+        self.network.validate(&mut issues);
+        self.tick.validate(&mut issues);
+        self.telemetry.validate(&mut issues);
+        self.logging.validate(&mut issues);
+        issues.into_inner()
+    }
+
+    /// Checks the values that can change while the server runs, the others
+    /// are checked against the running server, which holds its sockets
+    pub(crate) fn validate_reloadable(&self, issues: &mut Issues) {
+        if self.max_players == 0 {
+            issues.push("max_players", "must be at least 1");
+        }
+        if self.motd.len() > MAX_MOTD_LENGTH {
+            issues.push(
+                "motd",
+                format!("must be at most {MAX_MOTD_LENGTH} bytes long"),
+            );
+        }
+        self.logging.validate_reloadable(issues);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::logging::LogFileConfig;
+
+    #[test]
+    fn every_issue_is_collected() {
+        let mut config = Config {
+            max_players: 0,
+            motd: "a".repeat(MAX_MOTD_LENGTH + 1),
+            ..Config::default()
+        };
+        config.network.certs = "missing/certs.pem".into();
+        config.network.key = "missing/key.pem".into();
+        config.tick.rate = 0.;
+        config.logging.files = vec![LogFileConfig::default(), LogFileConfig::default()];
+
+        let paths: Vec<String> = config
+            .validate(&Sources::default())
+            .into_iter()
+            .inspect(|issue| assert_eq!(issue.source, Source::Default))
+            .map(|issue| issue.path)
+            .collect();
+        for path in [
+            "max_players",
+            "motd",
+            "network.certs",
+            "network.key",
+            "tick.rate",
+        ] {
+            assert!(
+                paths.iter().any(|issue| issue == path),
+                "{path} not reported"
+            );
+        }
+    }
+}
@@ -8,5 +8,47 @@
 mod command_receiver;
 mod event_sender;
 
-pub use command_receiver::{CommandReceiver, process_incoming_commands};
-pub use event_sender::{EventSender, process_outbound_events};
+pub use command_receiver::{
+    CommandReceiver, CommandSpans, Traced, process_incoming_commands, rotate_command_spans,
+};
+pub use event_sender::{EventSender, add_outbound_systems};
+
+use bevy::{
+    app::{App, First, PreUpdate},
+    ecs::{
+        event::{EventUpdates, event_update_condition},
+        schedule::{IntoScheduleConfigs, SystemSet},
+    },
+};
+
+/// The systems moving messages between the network and the game.
+///
+/// Commands are received in `PreUpdate`, before the fixed ticks and
+/// `Update` run. Events are sent in `FixedLast` at the end of every tick and
+/// in `PostUpdate`. An event written in response to a command therefore goes
+/// out in the same update.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemSet)]
+pub enum NetworkSet {
+    /// Writes the received commands as events, runs in `PreUpdate`
+    Receive,
+    /// Forwards the written events to the network, runs in `FixedLast` and
+    /// `PostUpdate`
+    Send,
+}
+
+/// Adds the systems moving commands into bevy and events out of it,
+/// shared by every network plugin
+pub fn add_bridge_systems(app: &mut App) {
+    app.init_resource::<CommandSpans>()
+        .add_systems(
+            First,
+            rotate_command_spans
+                .before(EventUpdates)
+                .run_if(event_update_condition),
+        )
+        .add_systems(
+            PreUpdate,
+            process_incoming_commands.in_set(NetworkSet::Receive),
+        );
+    add_outbound_systems(app);
+}
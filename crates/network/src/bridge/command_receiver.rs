@@ -2,40 +2,94 @@
 // Copyright (C) 2025 Crypts of the Lost Team
 
 //! # `CommandReceiver`
-//! Stores the rx from the networkhandler
+//! Stores the rx from the networkhandler, and the session spans of the
+//! commands written into bevy
 
-use bevy::ecs::{event::EventWriter, resource::Resource, system::ResMut};
-use protocol::command::{CommandKind, join::Join};
+use crate::NetworkMetrics;
+use bevy::ecs::{
+    event::{Event, EventId},
+    resource::Resource,
+    system::ResMut,
+    world::{Mut, World},
+};
+use protocol::command::CommandKind;
+use std::{any::TypeId, collections::HashMap};
 use tokio::sync::mpsc::UnboundedReceiver;
+use tracing::Span;
 
 #[derive(Debug, Resource)]
 pub struct CommandReceiver {
-    pub rx: UnboundedReceiver<CommandKind>,
+    pub rx: UnboundedReceiver<Traced<CommandKind>>,
 }
 
-macro_rules! handle_commands {
-    ($cmd:expr, { $($variant:ident => $writer:ident),* $(,)? }) => {
-        match $cmd {
-            $(
-                CommandKind::$variant(data) => {
-                    $writer.write(data);
-                }
-            )*
-            _ => {}
-        }
-    };
+/// A message together with the span of the session it was received on
+#[derive(Debug)]
+pub struct Traced<T> {
+    /// The `session` span, carrying the session id, the address and the uuid
+    /// of the player once joined
+    pub span: Span,
+    /// The received message
+    pub message: T,
 }
 
-const MAX_PER_TICK: u32 = 100;
+/// The session spans of the command events, so gameplay logs can be tied to
+/// the player that sent a command. Read the events with
+/// `EventReader::read_with_id` and enter the span of their id.
+///
+/// Like the events, the spans are kept for two event updates, see
+/// [`rotate_command_spans`].
+#[derive(Debug, Default, Resource)]
+pub struct CommandSpans {
+    /// Spans of the events written since the last event update
+    current: HashMap<(TypeId, usize), Span>,
+    /// Spans of the events written before the last event update
+    previous: HashMap<(TypeId, usize), Span>,
+}
+
+impl CommandSpans {
+    /// The span of the session the command was received on, or a disabled
+    /// span if it isn't known
+    #[must_use]
+    pub fn span<T: Event>(&self, id: EventId<T>) -> Span {
+        let key = (TypeId::of::<T>(), id.id);
+        self.current
+            .get(&key)
+            .or_else(|| self.previous.get(&key))
+            .cloned()
+            .unwrap_or_else(Span::none)
+    }
+}
 
-pub fn process_incoming_commands(mut recv: ResMut<CommandReceiver>, mut join: EventWriter<Join>) {
-    let mut processed = 0;
+/// Drops the spans of the events bevy is about to drop. Runs right before the
+/// events are updated in `First`, under the same condition, so with a fixed
+/// timestep the spans are kept as long as the events are.
+pub fn rotate_command_spans(mut spans: ResMut<CommandSpans>) {
+    spans.previous = std::mem::take(&mut spans.current);
+}
+
+const MAX_PER_TICK: u32 = 100;
 
-    while processed < MAX_PER_TICK {
-        let Ok(cmd) = recv.rx.try_recv() else { break };
-        handle_commands!(cmd, {
-            Join => join
+/// Writes the received commands as bevy events, every variant of
+/// `CommandKind` is handled by `CommandKind::write_event`.
+pub fn process_incoming_commands(world: &mut World) {
+    world.resource_scope(|world, mut recv: Mut<CommandReceiver>| {
+        world.resource_scope(|world, mut spans: Mut<CommandSpans>| {
+            let mut processed = 0;
+            while processed < MAX_PER_TICK {
+                let Ok(Traced { span, message }) = recv.rx.try_recv() else {
+                    break;
+                };
+                if let Some(key) = message.write_event(world)
+                    && !span.is_none()
+                {
+                    spans.current.insert(key, span);
+                }
+                processed += 1;
+            }
         });
-        processed += 1;
-    }
+
+        if let Some(metrics) = world.get_resource::<NetworkMetrics>() {
+            metrics.handler.set_inbound_queue(recv.rx.len());
+        }
+    });
 }
@@ -2,38 +2,172 @@
 // Copyright (C) 2025 Crypts of the Lost Team
 
 //! # `EventSender`
-//! Stores the tx to the networkhandler
+//! Stores the tx to the networkhandler, and forwards the events written in
+//! bevy to it
 
-use bevy::ecs::{event::EventReader, resource::Resource, system::Res};
-use protocol::event::{EventKind, JoinAccept, PlayerJoined};
+use super::NetworkSet;
+use bevy::{
+    app::{App, FixedLast, PostUpdate},
+    ecs::{
+        event::{Event, EventCursor, Events},
+        resource::Resource,
+        schedule::IntoScheduleConfigs,
+        world::{Mut, World},
+    },
+};
+use protocol::{MessageVisitor, ServerTick, Stamped, event::EventKind};
 use tokio::sync::mpsc::UnboundedSender;
 
 #[derive(Debug, Resource)]
 pub struct EventSender {
-    pub tx: UnboundedSender<EventKind>,
+    pub tx: UnboundedSender<Stamped<EventKind>>,
 }
 
-macro_rules! send_all_events {
-    ($sender:expr, $($reader:expr),+ $(,)?) => {
-        $(
-            send_events($sender, $reader);
-        )+
-    };
+/// Adds the system forwarding every event type to the network handler.
+///
+/// It runs at the end of every tick, so events of the fixed schedules are
+/// stamped with the tick they were written in, and in `PostUpdate` for the
+/// events written outside of the ticks.
+pub fn add_outbound_systems(app: &mut App) {
+    let mut outbound = Outbound::default();
+    EventKind::visit_variants(&mut outbound);
+    app.insert_resource(outbound)
+        .add_systems(FixedLast, send_events.in_set(NetworkSet::Send))
+        .add_systems(PostUpdate, send_events.in_set(NetworkSet::Send));
+}
+
+/// Reads the events of a single type
+trait Forward: Send + Sync {
+    /// Sends the events written since the last call
+    fn forward(
+        &mut self,
+        world: &World,
+        tick: ServerTick,
+        tx: &UnboundedSender<Stamped<EventKind>>,
+    );
 }
 
-pub fn process_outbound_events(
-    sender: Res<EventSender>,
-    mut join_accept: EventReader<JoinAccept>,
-    mut player_joined: EventReader<PlayerJoined>,
-) {
-    send_all_events!(&sender.tx, &mut join_accept, &mut player_joined);
+/// The reader of every event type, in the order of the `EventKind` variants
+#[derive(Default, Resource)]
+struct Outbound {
+    readers: Vec<Box<dyn Forward>>,
+}
+
+impl std::fmt::Debug for Outbound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Outbound")
+            .field("readers", &self.readers.len())
+            .finish()
+    }
 }
 
-fn send_events<T>(sender: &UnboundedSender<EventKind>, reader: &mut EventReader<T>)
+impl MessageVisitor<EventKind> for Outbound {
+    fn visit<T>(&mut self)
+    where
+        T: Event + Clone + Into<EventKind>,
+    {
+        self.readers.push(Box::new(EventCursor::<T>::default()));
+    }
+}
+
+impl<T> Forward for EventCursor<T>
 where
-    T: Clone + Into<EventKind> + bevy::prelude::Event + protocol::Event,
+    T: Event + Clone + Into<EventKind>,
 {
-    for event in reader.read() {
-        let _ = sender.send(event.clone().into());
+    fn forward(
+        &mut self,
+        world: &World,
+        tick: ServerTick,
+        tx: &UnboundedSender<Stamped<EventKind>>,
+    ) {
+        let Some(events) = world.get_resource::<Events<T>>() else {
+            return;
+        };
+        for event in self.read(events) {
+            let _ = tx.send(tick.stamp(event.clone().into()));
+        }
+    }
+}
+
+/// Forwards the events, stamped with the tick they were produced on.
+///
+/// The event types are sent in the order of the `EventKind` variants, so a
+/// `JoinAccept` always reaches a connection before the events written
+/// together with it.
+fn send_events(world: &mut World) {
+    world.resource_scope(|world, mut outbound: Mut<Outbound>| {
+        let Some(sender) = world.get_resource::<EventSender>() else {
+            return;
+        };
+        let tick = world
+            .get_resource::<ServerTick>()
+            .copied()
+            .unwrap_or_default();
+        for reader in &mut outbound.readers {
+            reader.forward(world, tick, &sender.tx);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::{event::EventWriter, system::ResMut};
+    use protocol::{
+        Protocol,
+        event::{JoinAccept, PlayerJoined},
+    };
+
+    #[test]
+    fn join_accept_is_sent_first() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut app = App::new();
+        app.add_plugins(Protocol)
+            .insert_resource(EventSender { tx })
+            .add_systems(bevy::app::Update, |world: &mut World| {
+                world.send_event(PlayerJoined {});
+                world.send_event(JoinAccept {
+                    connection: 1,
+                    uuid: 1,
+                    compression: None,
+                    dictionary: None,
+                });
+            });
+        add_outbound_systems(&mut app);
+        app.update();
+
+        let sent: Vec<_> = std::iter::from_fn(|| rx.try_recv().ok())
+            .map(|stamped| stamped.message.name())
+            .collect();
+        assert_eq!(sent, ["JoinAccept", "PlayerJoined"]);
+    }
+
+    #[test]
+    fn events_are_stamped_with_their_tick() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut app = App::new();
+        app.add_plugins(Protocol)
+            .insert_resource(EventSender { tx })
+            .add_systems(bevy::app::FixedFirst, |mut tick: ResMut<ServerTick>| {
+                tick.advance();
+            })
+            .add_systems(
+                bevy::app::FixedUpdate,
+                |mut joined: EventWriter<PlayerJoined>| {
+                    joined.write(PlayerJoined {});
+                },
+            );
+        add_outbound_systems(&mut app);
+
+        // three ticks caught up on in a single update
+        for _ in 0..3 {
+            app.world_mut().run_schedule(bevy::app::FixedMain);
+        }
+        app.update();
+
+        let ticks: Vec<_> = std::iter::from_fn(|| rx.try_recv().ok())
+            .map(|stamped| stamped.tick.0)
+            .collect();
+        assert_eq!(ticks, [1, 2, 3]);
     }
 }
@@ -0,0 +1,96 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # Capture
+//! Records the commands and events of every session into a capture file, so
+//! a session can be replayed later on. The format of the file is part of the
+//! [`wire`] format.
+
+use protocol::{Stamped, command::CommandKind, event::EventKind};
+use std::{
+    io::Write,
+    path::Path,
+    sync::mpsc::{self, Sender},
+    time::Instant,
+};
+use tracing::{error, info, warn};
+use wire::CaptureError;
+pub use wire::capture::{CaptureReader, CaptureWriter, MAGIC, Message, Record, VERSION, read_file};
+
+/// Records the messages of all sessions from the tasks of the connections.
+///
+/// The records are written to the capture by a thread of their own, which
+/// stops once every clone of the recorder is dropped.
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    tx: Sender<Record>,
+    started: Instant,
+}
+
+impl Recorder {
+    /// Creates the capture file and starts writing to it
+    ///
+    /// # Errors
+    /// Returns a `CaptureError` when the file can't be created.
+    pub fn create(path: impl AsRef<Path>) -> Result<Self, CaptureError> {
+        let path = path.as_ref();
+        let writer = CaptureWriter::create(path)?;
+        info!("recording sessions to {}", path.display());
+        Ok(Self::start(writer))
+    }
+
+    /// Starts writing to the capture.
+    ///
+    /// Records that can't be encoded or are too large are skipped with a
+    /// warning, recording only stops when writing to the capture fails.
+    pub fn start<W: Write + Send + 'static>(mut writer: CaptureWriter<W>) -> Self {
+        let (tx, rx) = mpsc::channel::<Record>();
+
+        std::thread::spawn(move || {
+            while let Ok(record) = rx.recv() {
+                // flush once the backlog is written, instead of after every record
+                let result = std::iter::once(record)
+                    .chain(rx.try_iter())
+                    .try_for_each(|record| match writer.write(&record) {
+                        Err(e @ CaptureError::Io(_)) => Err(e),
+                        // a single record that can't be written doesn't end the capture
+                        Err(e) => {
+                            warn!("skipped a record of session {}: {e}", record.session);
+                            Ok(())
+                        }
+                        Ok(()) => Ok(()),
+                    })
+                    .and_then(|()| writer.flush());
+
+                if let Err(e) = result {
+                    error!("stopped recording, wasn't able to write to the capture: {e}");
+                    return;
+                }
+            }
+        });
+
+        Self {
+            tx,
+            started: Instant::now(),
+        }
+    }
+
+    /// Records a command received from the session
+    pub fn inbound(&self, session: u64, command: &CommandKind) {
+        self.record(session, Message::Inbound(*command));
+    }
+
+    /// Records an event sent to the session
+    pub fn outbound(&self, session: u64, event: &Stamped<EventKind>) {
+        self.record(session, Message::Outbound(event.clone()));
+    }
+
+    fn record(&self, session: u64, message: Message) {
+        let at = u64::try_from(self.started.elapsed().as_micros()).unwrap_or(u64::MAX);
+        let _ = self.tx.send(Record {
+            at,
+            session,
+            message,
+        });
+    }
+}
@@ -5,10 +5,12 @@
 //! This module has some helper functions for working with certificates
 
 use quinn::ServerConfig;
+use quinn::crypto::rustls::QuicServerConfig;
 use quinn::rustls::pki_types::pem::PemObject;
 use quinn::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use quinn::rustls::{self, crypto::ring, version::TLS13};
 use rustls_pki_types::pem;
-use std::path::Path;
+use std::{path::Path, sync::Arc};
 
 use crate::error::CertsError;
 
@@ -54,11 +56,67 @@ impl Certs {
 
     /// Creates a [`ServerConfig`] to be used by the `NetworkHandler`
     ///
+    /// The server accepts the given ALPN protocols, which is how clients pick
+    /// a [`Codec`](crate::Codec).
+    ///
     /// # Errors
     /// Returns an `CertsError` when `ServerConfig` creation fails.
-    pub fn create_server_config(self) -> Result<ServerConfig, CertsError> {
-        Ok(ServerConfig::with_single_cert(self.certs, self.key)?)
+    pub fn create_server_config(
+        self,
+        alpn_protocols: Vec<Vec<u8>>,
+    ) -> Result<ServerConfig, CertsError> {
+        let crypto = self.create_tls_config(alpn_protocols)?;
+        let crypto = QuicServerConfig::try_from(crypto)?;
+        Ok(ServerConfig::with_crypto(Arc::new(crypto)))
+    }
+
+    /// Creates a TLS 1.3 [`rustls::ServerConfig`] accepting the given ALPN protocols,
+    /// used for transports other than QUIC.
+    ///
+    /// # Errors
+    /// Returns an `CertsError` when the certificate and key don't match.
+    pub fn create_tls_config(
+        &self,
+        alpn_protocols: Vec<Vec<u8>>,
+    ) -> Result<rustls::ServerConfig, CertsError> {
+        let mut crypto =
+            rustls::ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+                .with_protocol_versions(&[&TLS13])?
+                .with_no_client_auth()
+                .with_single_cert(self.certs.clone(), self.key.clone_key())?;
+        crypto.alpn_protocols = alpn_protocols;
+        Ok(crypto)
+    }
+
+    /// SHA-256 fingerprint of the server certificate, the first one in the
+    /// chain, as colon separated hex. Clients can pin it instead of trusting
+    /// a self-signed certificate blindly.
+    #[must_use]
+    pub fn fingerprint(&self) -> Option<String> {
+        self.certs.first().map(fingerprint)
     }
+
+    /// Reads the [`fingerprint`](Self::fingerprint) of the first certificate
+    /// in the file, without needing the private key
+    ///
+    /// # Errors
+    /// Returns an `pem::Error` if the file can't be read or parsed
+    pub fn read_fingerprint<P: AsRef<Path>>(cert_path: P) -> Result<Option<String>, pem::Error> {
+        Ok(CertificateDer::pem_file_iter(cert_path)?
+            .next()
+            .transpose()?
+            .as_ref()
+            .map(fingerprint))
+    }
+}
+
+fn fingerprint(cert: &CertificateDer) -> String {
+    ::ring::digest::digest(&::ring::digest::SHA256, cert)
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{byte:02X}"))
+        .collect::<Vec<_>>()
+        .join(":")
 }
 
 #[cfg(test)]
@@ -0,0 +1,283 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # Compression
+//! Negotiates and applies the per connection compression of outbound frames.
+//! The dictionaries and the decompression are part of the [`wire`] format.
+
+use config::config::network::CompressionConfig;
+use protocol::Compression;
+use std::{
+    fmt::Debug,
+    io,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+pub use wire::compression::{Dictionary, decompress};
+
+/// Counters about the compression of outbound frames
+#[derive(Debug, Default)]
+pub struct CompressionMetrics {
+    compressed_frames: AtomicU64,
+    uncompressed_bytes: AtomicU64,
+    compressed_bytes: AtomicU64,
+}
+
+impl CompressionMetrics {
+    fn record(&self, uncompressed: usize, compressed: usize) {
+        self.compressed_frames.fetch_add(1, Ordering::Relaxed);
+        self.uncompressed_bytes
+            .fetch_add(uncompressed as u64, Ordering::Relaxed);
+        self.compressed_bytes
+            .fetch_add(compressed as u64, Ordering::Relaxed);
+    }
+
+    /// Amount of frames that were sent compressed
+    #[must_use]
+    pub fn compressed_frames(&self) -> u64 {
+        self.compressed_frames.load(Ordering::Relaxed)
+    }
+
+    /// Size of the compressed frames before compression
+    #[must_use]
+    pub fn uncompressed_bytes(&self) -> u64 {
+        self.uncompressed_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Size of the compressed frames after compression
+    #[must_use]
+    pub fn compressed_bytes(&self) -> u64 {
+        self.compressed_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Bandwidth saved by compressing frames
+    #[must_use]
+    pub fn saved_bytes(&self) -> u64 {
+        self.uncompressed_bytes()
+            .saturating_sub(self.compressed_bytes())
+    }
+}
+
+/// Server wide compression settings, shared by all connections
+#[derive(Debug)]
+pub struct CompressionContext {
+    enabled: bool,
+    threshold: usize,
+    level: i32,
+    dictionary: Option<Dictionary>,
+    metrics: Arc<CompressionMetrics>,
+}
+
+impl CompressionContext {
+    /// Creates the compression settings from the config
+    ///
+    /// # Errors
+    /// Returns an `io::Error` when the configured dictionary can't be read.
+    pub fn from_config(config: &CompressionConfig) -> io::Result<Self> {
+        let dictionary = config
+            .dictionary
+            .as_ref()
+            .map(Dictionary::read_from_file)
+            .transpose()?;
+
+        Ok(Self {
+            enabled: config.enabled,
+            threshold: config.threshold,
+            level: config.level,
+            dictionary,
+            metrics: Arc::new(CompressionMetrics::default()),
+        })
+    }
+
+    /// The counters of all frames compressed with this context
+    #[must_use]
+    pub fn metrics(&self) -> Arc<CompressionMetrics> {
+        self.metrics.clone()
+    }
+
+    /// Decides on the compression for a client, based on what it requested.
+    ///
+    /// The dictionary is only used if the client has the same one as the server.
+    #[must_use]
+    pub fn negotiate(
+        &self,
+        requested: Option<Compression>,
+        dictionary: Option<u32>,
+    ) -> (Option<Compression>, Option<u32>) {
+        if !self.enabled {
+            return (None, None);
+        }
+
+        let Some(compression) = requested.filter(|compression| Self::is_supported(*compression))
+        else {
+            return (None, None);
+        };
+
+        let dictionary = dictionary.filter(|id| {
+            self.dictionary
+                .as_ref()
+                .is_some_and(|dictionary| dictionary.id() == *id)
+        });
+
+        (Some(compression), dictionary)
+    }
+
+    fn unsupported(compression: Compression) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("unsupported compression {compression:?}"),
+        )
+    }
+
+    const fn is_supported(compression: Compression) -> bool {
+        matches!(compression, Compression::Zstd | Compression::Lz4)
+    }
+
+    /// Creates the compressor for a connection after negotiation
+    ///
+    /// # Errors
+    /// Returns an `io::Error` when zstd fails to load the dictionary.
+    pub fn compressor(
+        &self,
+        compression: Compression,
+        dictionary: Option<u32>,
+    ) -> io::Result<Compressor> {
+        let dictionary = dictionary.and_then(|_| self.dictionary.clone());
+        let inner = match compression {
+            Compression::Zstd => {
+                let dictionary = dictionary.as_ref().map_or(&[][..], Dictionary::data);
+                Inner::Zstd(zstd::bulk::Compressor::with_dictionary(
+                    self.level, dictionary,
+                )?)
+            }
+            Compression::Lz4 => Inner::Lz4(dictionary),
+            _ => return Err(Self::unsupported(compression)),
+        };
+
+        Ok(Compressor {
+            inner,
+            threshold: self.threshold,
+            metrics: self.metrics.clone(),
+        })
+    }
+}
+
+enum Inner {
+    Zstd(zstd::bulk::Compressor<'static>),
+    Lz4(Option<Dictionary>),
+}
+
+/// Compresses the outbound frames of a single connection
+pub struct Compressor {
+    inner: Inner,
+    threshold: usize,
+    metrics: Arc<CompressionMetrics>,
+}
+
+impl Debug for Compressor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let algorithm = match self.inner {
+            Inner::Zstd(_) => Compression::Zstd,
+            Inner::Lz4(_) => Compression::Lz4,
+        };
+        f.debug_struct("Compressor")
+            .field("algorithm", &algorithm)
+            .field("threshold", &self.threshold)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Compressor {
+    /// Compresses the payload if it is at least as large as the threshold.
+    ///
+    /// Returns `None` when the payload should be sent uncompressed, either
+    /// because it is too small or because compressing didn't make it smaller.
+    ///
+    /// # Errors
+    /// Returns an `io::Error` when zstd fails to compress.
+    pub fn compress(&mut self, data: &[u8]) -> io::Result<Option<Vec<u8>>> {
+        if data.len() < self.threshold {
+            return Ok(None);
+        }
+
+        let compressed = match &mut self.inner {
+            Inner::Zstd(compressor) => compressor.compress(data)?,
+            Inner::Lz4(Some(dictionary)) => {
+                lz4_flex::block::compress_prepend_size_with_dict(data, dictionary.data())
+            }
+            Inner::Lz4(None) => lz4_flex::compress_prepend_size(data),
+        };
+
+        if compressed.len() >= data.len() {
+            return Ok(None);
+        }
+
+        self.metrics.record(data.len(), compressed.len());
+        Ok(Some(compressed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(dictionary: Option<Dictionary>) -> CompressionContext {
+        CompressionContext {
+            enabled: true,
+            threshold: 16,
+            level: 3,
+            dictionary,
+            metrics: Arc::new(CompressionMetrics::default()),
+        }
+    }
+
+    #[test]
+    fn round_trip() -> io::Result<()> {
+        let dictionary = Dictionary::new(b"crypts of the lost ".repeat(8));
+        let data = b"crypts of the lost inventory snapshot ".repeat(64);
+
+        for compression in [Compression::Zstd, Compression::Lz4] {
+            for dictionary in [None, Some(dictionary.clone())] {
+                let context = context(dictionary.clone());
+                let id = dictionary.as_ref().map(Dictionary::id);
+                let mut compressor = context.compressor(compression, id)?;
+
+                let compressed = compressor.compress(&data)?;
+                let compressed = compressed.ok_or_else(|| io::Error::other("not compressed"))?;
+                let decompressed =
+                    decompress(compression, dictionary.as_ref(), &compressed, data.len())?;
+
+                assert_eq!(decompressed, data);
+                assert!(context.metrics().saved_bytes() > 0);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn below_threshold() -> io::Result<()> {
+        let mut compressor = context(None).compressor(Compression::Zstd, None)?;
+        assert_eq!(compressor.compress(b"small")?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn negotiate_dictionary() {
+        let dictionary = Dictionary::new(&b"dictionary"[..]);
+        let id = dictionary.id();
+        let context = context(Some(dictionary));
+
+        assert_eq!(
+            context.negotiate(Some(Compression::Lz4), Some(id)),
+            (Some(Compression::Lz4), Some(id))
+        );
+        assert_eq!(
+            context.negotiate(Some(Compression::Lz4), Some(id + 1)),
+            (Some(Compression::Lz4), None)
+        );
+        assert_eq!(context.negotiate(None, Some(id)), (None, None));
+    }
+}
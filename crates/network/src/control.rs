@@ -0,0 +1,80 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # Control
+//! Resources to inspect and stop the `NetworkHandler` from the bevy world.
+
+use bevy::ecs::resource::Resource;
+use std::{
+    net::SocketAddr,
+    sync::{Arc, PoisonError, RwLock},
+};
+use tokio::sync::oneshot;
+
+/// The address the network handler is bound to, inserted as a resource by
+/// the `Network` plugin.
+///
+/// Differs from the configured sockets when their port is 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Resource)]
+pub struct NetworkAddress {
+    /// Address of the QUIC endpoint
+    pub socket: SocketAddr,
+    /// Address of the WebSocket gateway, when it is enabled
+    pub websocket: Option<SocketAddr>,
+}
+
+/// Stops the network handler, closing all connections.
+///
+/// Inserted as a resource by the `Network` plugin. The handler is also
+/// stopped when this is dropped, for example together with the `App`.
+#[derive(Debug, Resource)]
+pub struct NetworkShutdown {
+    tx: Option<oneshot::Sender<()>>,
+}
+
+impl NetworkShutdown {
+    pub(crate) const fn new(tx: oneshot::Sender<()>) -> Self {
+        Self { tx: Some(tx) }
+    }
+
+    /// Stops the network handler
+    pub fn shutdown(&mut self) {
+        if let Some(tx) = self.tx.take() {
+            let _ = tx.send(());
+        }
+    }
+}
+
+/// Whether the network handler accepts connections, inserted as a resource
+/// by the `Network` plugin and updated by the task running the handler.
+#[derive(Debug, Clone, Default, Resource)]
+pub struct NetworkStatus {
+    state: Arc<RwLock<NetworkState>>,
+}
+
+/// State of the network handler
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum NetworkState {
+    /// The handler isn't bound yet
+    #[default]
+    Starting,
+    /// The endpoint is bound and connections are accepted
+    Running,
+    /// The handler stopped or couldn't start, with the reason
+    Stopped(String),
+}
+
+impl NetworkStatus {
+    /// The current state of the handler
+    #[must_use]
+    pub fn state(&self) -> NetworkState {
+        self.state
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    pub(crate) fn set(&self, state: NetworkState) {
+        *self.state.write().unwrap_or_else(PoisonError::into_inner) = state;
+    }
+}
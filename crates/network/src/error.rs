@@ -5,6 +5,7 @@
 //! Defines the error type for the network crate
 
 use thiserror::Error;
+use wire::CodecError;
 
 /// Error type used by [`crate::handler::NetworkHandler`]
 #[derive(Debug, Error)]
@@ -12,15 +13,15 @@ pub enum HandlerError {
     /// Error from IO
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
-    /// Bincode `DecodeError`
-    #[error("DecodeError: {0}")]
-    Deserialize(#[from] rmp_serde::decode::Error),
-    /// Bincode `EncodeError`,
-    #[error("EncodeError: {0}")]
-    Encode(#[from] rmp_serde::encode::Error),
+    /// Error while encoding or decoding a message
+    #[error("CodecError: {0}")]
+    Codec(#[from] CodecError),
     /// `ConnectionError` from quinn
     #[error("ConnectionError: {0}")]
     Connection(#[from] quinn::ConnectionError),
+    /// A message encoded larger than the payload of a frame can be
+    #[error("message too large: {0} bytes")]
+    MessageTooLarge(usize),
 }
 
 /// Error type used by [`crate::Certs`]
@@ -32,4 +33,7 @@ pub enum CertsError {
     /// Error when creating a `ServerConfig` using the read certs and key
     #[error("serverconfig error: {0}")]
     Quinn(#[from] quinn::crypto::rustls::Error),
+    /// Error when the TLS config doesn't support the cipher suite QUIC needs
+    #[error("serverconfig error: {0}")]
+    CipherSuite(#[from] quinn::crypto::rustls::NoInitialCipherSuite),
 }
@@ -9,16 +9,29 @@ mod client;
 mod deserialize;
 mod handle_connection;
 mod inbound;
+mod migration;
 mod outbound;
 mod serialize;
+mod session;
 mod shutdown;
 mod start;
+mod status;
+#[cfg(feature = "websocket")]
+mod websocket;
 
+use crate::{
+    bridge::Traced, capture::Recorder, compression::CompressionContext, metrics::HandlerMetrics,
+};
+use client::Client;
 use dashmap::DashMap;
-use protocol::{command::CommandKind, event::EventKind};
-use quinn::{Connection, Endpoint, ServerConfig};
+use protocol::{Stamped, command::CommandKind, event::EventKind};
+use quinn::{Endpoint, ServerConfig};
+pub use status::ServerInfo;
 use std::net::SocketAddr;
-use std::sync::Arc;
+#[cfg(feature = "websocket")]
+use std::sync::atomic::AtomicU64;
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
 use tokio::sync::{
     broadcast::{self, Sender},
     mpsc::{UnboundedReceiver, UnboundedSender},
@@ -29,16 +42,43 @@ use tokio::sync::{
 pub struct NetworkHandler {
     /// The QUIC endpoint for handling connections
     endpoint: Option<Endpoint>,
-    /// Active connections mapped by client Address
-    connections: Arc<DashMap<SocketAddr, Connection>>,
-    /// Channel for sending inbound message to the dispatcher
-    inbound_tx: UnboundedSender<CommandKind>,
-    /// Fan out of the `outbound_rx`
-    broadcast: Sender<EventKind>,
+    /// State shared with the tasks of every connection
+    shared: Shared,
     /// Server configuration for QUIC
     server_config: ServerConfig,
     /// Socket address to bind to
     socket: SocketAddr,
+    /// Socket address and TLS configuration of the WebSocket gateway
+    #[cfg(feature = "websocket")]
+    websocket: Option<(SocketAddr, Arc<quinn::rustls::ServerConfig>)>,
+    /// Listener of the WebSocket gateway, once bound
+    #[cfg(feature = "websocket")]
+    websocket_listener: Option<std::net::TcpListener>,
+}
+
+/// State shared between the handler and the tasks of all connections,
+/// independent of their transport
+#[derive(Debug, Clone)]
+struct Shared {
+    /// Active connections mapped by their id
+    connections: Arc<DashMap<u64, Client>>,
+    /// Channel for sending inbound message to the dispatcher
+    inbound_tx: UnboundedSender<Traced<CommandKind>>,
+    /// Fan out of the `outbound_rx`
+    broadcast: Sender<Stamped<EventKind>>,
+    /// Compression settings shared by all connections
+    compression: Arc<CompressionContext>,
+    /// The id given to the next WebSocket connection
+    #[cfg(feature = "websocket")]
+    next_id: Arc<AtomicU64>,
+    /// Information about the server, reported in status queries
+    info: Arc<RwLock<ServerInfo>>,
+    /// When the handler was created, used for the uptime
+    started: Instant,
+    /// Records the messages of every session, when enabled
+    recorder: Option<Recorder>,
+    /// Counters of the connections and their messages
+    metrics: Arc<HandlerMetrics>,
 }
 
 impl NetworkHandler {
@@ -51,27 +91,80 @@ impl NetworkHandler {
     pub fn new(
         socket: SocketAddr,
         server_config: ServerConfig,
-        outbound_rx: UnboundedReceiver<EventKind>,
-        inbound_tx: UnboundedSender<CommandKind>,
+        outbound_rx: UnboundedReceiver<Stamped<EventKind>>,
+        inbound_tx: UnboundedSender<Traced<CommandKind>>,
+        compression: CompressionContext,
+        info: ServerInfo,
     ) -> Self {
-        let broadcast = Self::start_fan_out(outbound_rx);
+        let metrics = Arc::new(HandlerMetrics::default());
+        let broadcast = Self::start_fan_out(outbound_rx, metrics.clone());
         Self {
             endpoint: None,
-            connections: Arc::new(DashMap::new()),
-            inbound_tx,
-            broadcast,
+            shared: Shared {
+                connections: Arc::new(DashMap::new()),
+                inbound_tx,
+                broadcast,
+                compression: Arc::new(compression),
+                #[cfg(feature = "websocket")]
+                next_id: Arc::new(AtomicU64::new(1)),
+                info: Arc::new(RwLock::new(info)),
+                started: Instant::now(),
+                recorder: None,
+                metrics,
+            },
             server_config,
             socket,
+            #[cfg(feature = "websocket")]
+            websocket: None,
+            #[cfg(feature = "websocket")]
+            websocket_listener: None,
         }
     }
 
-    fn start_fan_out(mut outbound_rx: UnboundedReceiver<EventKind>) -> Sender<EventKind> {
-        let (broadcast_tx, _) = broadcast::channel::<EventKind>(1024);
+    /// Also accepts WebSocket connections on the given socket, using TLS.
+    #[cfg(feature = "websocket")]
+    #[must_use]
+    pub fn with_websocket(
+        mut self,
+        socket: SocketAddr,
+        tls_config: Arc<quinn::rustls::ServerConfig>,
+    ) -> Self {
+        self.websocket = Some((socket, tls_config));
+        self
+    }
+
+    /// Records the commands and events of every session with the given recorder.
+    #[must_use]
+    pub fn with_recorder(mut self, recorder: Recorder) -> Self {
+        self.shared.recorder = Some(recorder);
+        self
+    }
+
+    /// The information reported in status queries, can be changed while
+    /// the handler runs
+    #[must_use]
+    pub fn info(&self) -> Arc<RwLock<ServerInfo>> {
+        self.shared.info.clone()
+    }
+
+    /// The counters of the connections and messages of this handler
+    #[must_use]
+    pub fn metrics(&self) -> Arc<HandlerMetrics> {
+        self.shared.metrics.clone()
+    }
+
+    fn start_fan_out(
+        mut outbound_rx: UnboundedReceiver<Stamped<EventKind>>,
+        metrics: Arc<HandlerMetrics>,
+    ) -> Sender<Stamped<EventKind>> {
+        let (broadcast_tx, _) = broadcast::channel::<Stamped<EventKind>>(1024);
 
         let broadcast_tx_clone = broadcast_tx.clone();
         tokio::spawn(async move {
             while let Some(msg) = outbound_rx.recv().await {
                 let _ = broadcast_tx_clone.send(msg);
+                metrics.set_outbound_queue(outbound_rx.len());
+                metrics.set_broadcast_queue(broadcast_tx_clone.len());
             }
         });
 
@@ -1,33 +1,150 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 // Copyright (C) 2025 Crypts of the Lost Team
 
-use super::NetworkHandler;
-use dashmap::DashMap;
-use quinn::Connection;
-use std::{collections::HashSet, net::SocketAddr, sync::Arc};
+use super::{NetworkHandler, Shared};
+use quinn::{Connection, VarInt};
+#[cfg(feature = "websocket")]
+use std::sync::atomic::Ordering;
+use std::{collections::HashSet, net::SocketAddr};
+use tracing::{Span, field, info, info_span};
+
+/// A connected client together with the last address it was seen on
+#[derive(Debug, Clone)]
+pub struct Client {
+    /// The connection, stays the same when the client migrates
+    pub transport: Transport,
+    /// The most recently observed remote address of the connection
+    pub addr: SocketAddr,
+    /// The `session` span, every task of the connection runs in it
+    pub span: Span,
+    /// The uuid of the player, once it joined
+    pub player: Option<u64>,
+}
+
+/// The transport a client is connected with
+#[derive(Debug, Clone)]
+pub enum Transport {
+    /// A QUIC connection
+    Quic(Connection),
+    /// A WebSocket connection, closed by cancelling the token
+    #[cfg(feature = "websocket")]
+    WebSocket(tokio_util::sync::CancellationToken),
+}
+
+impl Client {
+    /// Closes the connection with the client
+    pub fn close(&self, error_code: u32, reason: &[u8]) {
+        match &self.transport {
+            Transport::Quic(connection) => connection.close(VarInt::from_u32(error_code), reason),
+            #[cfg(feature = "websocket")]
+            Transport::WebSocket(token) => token.cancel(),
+        }
+    }
+}
 
 impl NetworkHandler {
-    /// Adds a new client connection to the handler
-    pub(super) fn add_client(&self, addr: SocketAddr, conn: Connection) {
-        self.connections.insert(addr, conn);
+    /// Adds a new client connection to the handler and returns its id and
+    /// its `session` span.
+    ///
+    /// QUIC connections are identified by quinn's stable connection id.
+    /// Unlike the remote address, it stays the same for the entire lifetime
+    /// of the connection, even when the client migrates. WebSocket
+    /// connections get the next free id of a counter. The span carries the
+    /// id and the address, and the uuid of the player once it joined. It is
+    /// a root span, every session is a trace of its own.
+    pub(super) fn add_client(
+        shared: &Shared,
+        transport: Transport,
+        addr: SocketAddr,
+    ) -> (u64, Span) {
+        let id = match &transport {
+            Transport::Quic(connection) => Self::connection_id(connection),
+            #[cfg(feature = "websocket")]
+            Transport::WebSocket(_) => Self::next_free_id(shared),
+        };
+        let span = info_span!(
+            parent: None,
+            "session",
+            session = id,
+            uuid = field::Empty,
+            addr = %addr,
+        );
+        shared.connections.insert(
+            id,
+            Client {
+                transport,
+                addr,
+                span: span.clone(),
+                player: None,
+            },
+        );
+        shared.metrics.connection_opened();
+        (id, span)
+    }
+
+    /// Returns the id quinn uses to identify a connection for its entire lifetime
+    #[inline]
+    #[must_use]
+    fn connection_id(connection: &Connection) -> u64 {
+        connection.stable_id() as u64
+    }
+
+    /// Returns the next id of the counter that isn't used by a connection
+    #[cfg(feature = "websocket")]
+    fn next_free_id(shared: &Shared) -> u64 {
+        loop {
+            let id = shared.next_id.fetch_add(1, Ordering::Relaxed);
+            if !shared.connections.contains_key(&id) {
+                return id;
+            }
+        }
+    }
+
+    /// Updates the recorded address of a client if it migrated to a new one.
+    ///
+    /// Returns `false` when the client isn't tracked anymore.
+    pub(super) fn update_client_address(shared: &Shared, id: u64, addr: SocketAddr) -> bool {
+        let Some(mut client) = shared.connections.get_mut(&id) else {
+            return false;
+        };
+
+        if client.addr != addr {
+            info!("connection {id} migrated from {} to {addr}", client.addr);
+            client.span.record("addr", field::display(addr));
+            client.addr = addr;
+        }
+
+        true
+    }
+
+    /// Records the uuid of the player that joined on a connection
+    pub(super) fn set_player(shared: &Shared, id: u64, uuid: u64) {
+        if let Some(mut client) = shared.connections.get_mut(&id) {
+            client.player = Some(uuid);
+        }
+    }
+
+    /// Returns the most recently observed address of a client
+    pub(super) fn client_address(shared: &Shared, id: u64) -> Option<SocketAddr> {
+        shared.connections.get(&id).map(|client| client.addr)
     }
 
     /// Removes a client connection from the handler
-    pub(super) fn remove_client(
-        connections: &Arc<DashMap<SocketAddr, Connection>>,
-        addr: SocketAddr,
-        error_code: u32,
-        reason: &[u8],
-    ) {
-        let Some((_, connection)) = connections.remove(&addr) else {
+    pub(super) fn remove_client(shared: &Shared, id: u64, error_code: u32, reason: &[u8]) {
+        let Some((_, client)) = shared.connections.remove(&id) else {
             return;
         };
-        connection.close(error_code.into(), reason);
+        shared.metrics.connection_closed();
+        client.close(error_code, reason);
     }
 
     /// Gets all currently connected client addresses
     #[must_use]
     pub fn get_clients(&self) -> HashSet<SocketAddr> {
-        self.connections.iter().map(|item| *item.key()).collect()
+        self.shared
+            .connections
+            .iter()
+            .map(|item| item.addr)
+            .collect()
     }
 }
@@ -2,16 +2,19 @@
 // Copyright (C) 2025 Crypts of the Lost Team
 
 use protocol::command::CommandKind;
-use rmp_serde::{decode, from_slice};
 use tracing::trace;
 
 use super::NetworkHandler;
+use crate::{Codec, CodecError};
 
 impl NetworkHandler {
     #[tracing::instrument]
-    pub(super) fn deserialize_command(data: &[u8]) -> Result<CommandKind, decode::Error> {
+    pub(super) fn deserialize_command(
+        codec: Codec,
+        data: &[u8],
+    ) -> Result<CommandKind, CodecError> {
         trace!("deserializing command");
-        let cmd = from_slice(data)?;
+        let cmd = codec.decode(data)?;
         Ok(cmd)
     }
 }
@@ -1,40 +1,65 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 // Copyright (C) 2025 Crypts of the Lost Team
 
-use super::NetworkHandler;
-use dashmap::DashMap;
-use protocol::{command::CommandKind, event::EventKind};
-use quinn::Connection;
-use std::{net::SocketAddr, sync::Arc};
-use tokio::sync::{broadcast::Receiver, mpsc::UnboundedSender};
-use tracing::{error, info};
+use super::{NetworkHandler, Shared, client::Transport};
+use crate::{Codec, STATUS_ALPN};
+use quinn::{Connection, crypto::rustls::HandshakeData};
+use tracing::{Instrument, error, warn};
 
 impl NetworkHandler {
-    #[tracing::instrument]
-    pub(super) async fn handle_connection(
-        connection: Connection,
-        connections: Arc<DashMap<SocketAddr, Connection>>,
-        handler_tx: UnboundedSender<CommandKind>,
-        handler_rx: Receiver<EventKind>,
-    ) {
+    pub(super) async fn handle_connection(connection: Connection, shared: Shared) {
         let addr = connection.remote_address();
-        let Ok((tx, rx)) = connection.open_bi().await else {
-            error!("error opening bidirectional stream for client {addr}");
+        if Self::negotiated_protocol(&connection).as_deref() == Some(STATUS_ALPN) {
+            if let Err(e) = Self::handle_status(connection, &shared).await {
+                warn!("error answering status query from {addr}: {e}");
+            }
             return;
-        };
+        }
+
+        let (id, span) = Self::add_client(&shared, Transport::Quic(connection.clone()), addr);
+        Self::run_quic_session(connection, shared, id)
+            .instrument(span)
+            .await;
+    }
 
-        let inbound =
-            tokio::spawn(async move { Self::process_inbound(handler_tx, rx, addr).await });
+    async fn run_quic_session(connection: Connection, shared: Shared, id: u64) {
+        let codec = Self::negotiated_codec(&connection);
+
+        // the client opens the stream, the server can't announce a stream it
+        // opened itself until it has something to write
+        let Ok((tx, rx)) = connection.accept_bi().await else {
+            error!("client didn't open a bidirectional stream");
+            shared.metrics.connection_rejected();
+            Self::remove_client(&shared, id, 0, b"Failed to open stream");
+            return;
+        };
 
-        let outbound =
-            tokio::spawn(async move { Self::process_outbound(handler_rx, tx, addr).await });
+        let migration = tokio::spawn(
+            Self::watch_migration(connection.clone(), shared.clone(), id).in_current_span(),
+        );
 
-        let result = tokio::select! {
-            _ = inbound => "inbound",
-            _ = outbound => "outbound",
+        let closed = connection.clone();
+        let closed = async move {
+            closed.closed().await;
         };
 
-        info!("cleaning up connections for {addr} (reason: {result} ended");
-        Self::remove_client(&connections, addr, 0, b"Connection handler ended");
+        Self::run_session(shared, id, codec, closed, rx, tx).await;
+        migration.abort();
+    }
+
+    /// Returns the codec the client picked using ALPN, or the default one
+    /// if it didn't ask for any.
+    fn negotiated_codec(connection: &Connection) -> Codec {
+        Self::negotiated_protocol(connection)
+            .and_then(|protocol| Codec::from_alpn(&protocol))
+            .unwrap_or_default()
+    }
+
+    /// Returns the ALPN protocol the client picked, if any
+    fn negotiated_protocol(connection: &Connection) -> Option<Vec<u8>> {
+        connection
+            .handshake_data()
+            .and_then(|data| data.downcast::<HandshakeData>().ok())
+            .and_then(|data| data.protocol)
     }
 }
@@ -1,56 +1,75 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 // Copyright (C) 2025 Crypts of the Lost Team
 
-use std::net::SocketAddr;
-
-use super::NetworkHandler;
+use super::{NetworkHandler, Shared};
+use crate::bridge::Traced;
+use crate::{
+    Codec,
+    frame::{COMPRESSED_FLAG, MAX_MESSAGE_SIZE},
+};
 use protocol::command::CommandKind;
-use quinn::{ReadExactError, RecvStream};
-use tokio::sync::mpsc::UnboundedSender;
-use tracing::{error, warn};
+use std::io;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tracing::{Span, error, warn};
 
-type RecvResult = Option<Result<Vec<u8>, ReadExactError>>;
+type RecvResult = Option<Result<Vec<u8>, io::Error>>;
 
 impl NetworkHandler {
-    #[tracing::instrument]
-    pub(super) async fn process_inbound(
-        dispatcher_tx: UnboundedSender<CommandKind>,
-        mut conn_rx: RecvStream,
-        addr: SocketAddr,
+    /// Reads the commands of a connection and hands them to the dispatcher,
+    /// together with the `session` span it runs in
+    pub(super) async fn process_inbound<R: AsyncRead + Unpin>(
+        shared: Shared,
+        mut conn_rx: R,
+        id: u64,
+        codec: Codec,
     ) {
-        let id = conn_rx.id();
         while let Some(data) = Self::receive_command(&mut conn_rx).await {
             let Ok(data) = data else {
-                continue;
+                break;
             };
-            let Ok(mut cmd) = Self::deserialize_command(&data) else {
-                warn!(
-                    "[Stream {id}] wasn't able to deserialize following data to `Command`: {data:?}"
-                );
+            let Ok(mut cmd) = Self::deserialize_command(codec, &data) else {
+                shared.metrics.deserialize_failed();
+                warn!("wasn't able to deserialize following data to `Command`: {data:?}");
                 continue;
             };
 
+            shared.metrics.command_received(&cmd, data.len() + 4);
+
             if let CommandKind::Join(mut join) = cmd {
-                join.ip = Some(addr);
+                join.ip = Self::client_address(&shared, id);
+                join.connection = Some(id);
+                (join.compression, join.dictionary) = shared
+                    .compression
+                    .negotiate(join.compression, join.dictionary);
                 cmd = CommandKind::Join(join);
             }
 
-            if let Err(e) = dispatcher_tx.send(cmd) {
-                warn!("[Stream {id}] failed to send data to dispatcher: {e}");
+            if let Some(recorder) = &shared.recorder {
+                recorder.inbound(id, &cmd);
+            }
+
+            let traced = Traced {
+                span: Span::current(),
+                message: cmd,
+            };
+            if let Err(e) = shared.inbound_tx.send(traced) {
+                warn!("failed to send data to dispatcher: {e}");
             }
         }
     }
 
-    pub(super) const MAX_MESSAGE_SIZE: u32 = 1024 * 1024;
-
-    async fn receive_command(stream: &mut quinn::RecvStream) -> RecvResult {
+    async fn receive_command<R: AsyncRead + Unpin>(stream: &mut R) -> RecvResult {
         let mut len_buf = [0u8; 4];
         if let Err(e) = Self::read_exact(stream, &mut len_buf).await {
             return e;
         }
 
         let len = u32::from_be_bytes(len_buf);
-        if len > Self::MAX_MESSAGE_SIZE {
+        if len & COMPRESSED_FLAG != 0 {
+            warn!("Received a compressed frame, only the server may compress frames");
+            return None;
+        }
+        if len > MAX_MESSAGE_SIZE {
             warn!("Message to large: {len} bytes");
             return None;
         }
@@ -63,16 +82,18 @@ impl NetworkHandler {
         Some(Ok(data))
     }
 
-    async fn read_exact(stream: &mut quinn::RecvStream, buf: &mut [u8]) -> Result<(), RecvResult> {
-        let id = stream.id();
+    async fn read_exact<R: AsyncRead + Unpin>(
+        stream: &mut R,
+        buf: &mut [u8],
+    ) -> Result<(), RecvResult> {
         match stream.read_exact(buf).await {
-            Ok(()) => Ok(()),
-            Err(ReadExactError::FinishedEarly(n)) => {
-                error!("[Stream {id}] EOF ({n} bytes read)");
+            Ok(_) => Ok(()),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                error!("EOF");
                 Err(None)
             }
             Err(e) => {
-                warn!("[Stream {id}] error during read: {e}");
+                warn!("error during read: {e}");
                 Err(Some(Err(e)))
             }
         }
@@ -0,0 +1,33 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+use super::{NetworkHandler, Shared};
+use quinn::Connection;
+use std::time::Duration;
+
+impl NetworkHandler {
+    /// How often the remote address of a connection gets checked for migrations
+    const MIGRATION_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+    /// Keeps the recorded address of a client up to date when the peer
+    /// migrates, for example when a mobile client switches networks or
+    /// a NAT rebinds its port.
+    ///
+    /// Quinn doesn't notify about migrations, so the remote address is polled
+    /// until the connection closes or the client is removed.
+    #[tracing::instrument(skip_all)]
+    pub(super) async fn watch_migration(connection: Connection, shared: Shared, id: u64) {
+        let mut interval = tokio::time::interval(Self::MIGRATION_CHECK_INTERVAL);
+
+        loop {
+            tokio::select! {
+                _ = connection.closed() => return,
+                _ = interval.tick() => {
+                    if !Self::update_client_address(&shared, id, connection.remote_address()) {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
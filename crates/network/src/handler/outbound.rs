@@ -1,54 +1,114 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 // Copyright (C) 2025 Crypts of the Lost Team
 
-use super::NetworkHandler;
+use super::{NetworkHandler, Shared};
+use crate::{
+    Codec,
+    compression::Compressor,
+    frame::{COMPRESSED_FLAG, MAX_MESSAGE_SIZE},
+};
 use protocol::{Targetable, event::EventKind};
-use std::net::SocketAddr;
-use tokio::sync::broadcast::Receiver;
-use tracing::{error, warn};
+use tokio::{
+    io::{AsyncWrite, AsyncWriteExt},
+    sync::broadcast::error::RecvError,
+};
+use tracing::{Span, error, warn};
 
 impl NetworkHandler {
-    #[tracing::instrument]
-    pub(super) async fn process_outbound(
-        mut dispatcher_rx: Receiver<EventKind>,
-        mut conn_tx: quinn::SendStream,
-        addr: SocketAddr,
+    /// Writes the events meant for a connection, recording the uuid on the
+    /// client and the `session` span it runs in once the player joined
+    pub(super) async fn process_outbound<W: AsyncWrite + Unpin>(
+        shared: Shared,
+        mut conn_tx: W,
+        id: u64,
+        codec: Codec,
     ) {
-        let id = conn_tx.id();
+        let mut dispatcher_rx = shared.broadcast.subscribe();
         let mut uuid = 0;
-        while let Ok(event) = dispatcher_rx.recv().await {
-            if let EventKind::JoinAccept(join_accept) = &event {
-                if join_accept.ip != addr {
+        let mut compressor: Option<Compressor> = None;
+        loop {
+            let stamped = match dispatcher_rx.recv().await {
+                Ok(stamped) => stamped,
+                Err(RecvError::Lagged(skipped)) => {
+                    shared.metrics.lagged(skipped);
+                    warn!("fell behind, skipped {skipped} events");
+                    return;
+                }
+                Err(RecvError::Closed) => return,
+            };
+            let event = &stamped.message;
+
+            let negotiated = if let EventKind::JoinAccept(join_accept) = event {
+                if join_accept.connection != id {
                     continue;
                 }
                 uuid = join_accept.uuid;
-            }
+                Span::current().record("uuid", uuid);
+                Self::set_player(&shared, id, uuid);
+                join_accept
+                    .compression
+                    .map(|algorithm| (algorithm, join_accept.dictionary))
+            } else {
+                None
+            };
 
             if !event.is_recipient(&uuid) {
                 continue;
             }
 
-            let Ok(data) = Self::serialize_event(&event) else {
+            if let Some(recorder) = &shared.recorder {
+                recorder.outbound(id, &stamped);
+            }
+
+            let Ok(data) = Self::serialize_event(codec, event) else {
+                shared.metrics.serialize_failed();
                 warn!("wasn't able to serialize event");
                 continue;
             };
 
+            let (data, compressed) = match compressor.as_mut().map(|c| c.compress(&data)) {
+                Some(Ok(Some(compressed))) => (compressed, true),
+                Some(Err(e)) => {
+                    warn!("wasn't able to compress event: {e}");
+                    (data, false)
+                }
+                _ => (data, false),
+            };
+
             let data_length = data.len();
-            if data_length > Self::MAX_MESSAGE_SIZE as usize {
+            if data_length > MAX_MESSAGE_SIZE as usize {
                 warn!("Message to large: {data_length} bytes");
                 continue;
             }
 
             #[expect(clippy::cast_possible_truncation)]
-            let len = u32::to_be_bytes(data_length as u32); // won't run if size is over 1MB which is under the max u32 size
+            let mut len = data_length as u32; // won't run if size is over 1MB which is under the max u32 size
+            if compressed {
+                len |= COMPRESSED_FLAG;
+            }
+            let len = len.to_be_bytes();
             let mut buf = Vec::with_capacity(data_length + 4);
             buf.extend_from_slice(&len);
             buf.extend_from_slice(&data);
 
             if let Err(e) = conn_tx.write_all(&buf).await {
-                error!("[Stream {id}] error writing to the stream: {e}");
+                error!("error writing to the stream: {e}");
+                return;
+            }
+            if let Err(e) = conn_tx.flush().await {
+                error!("error flushing the stream: {e}");
                 return;
             }
+            shared.metrics.event_sent(event, buf.len());
+
+            // the `JoinAccept` itself is never compressed, so the client knows
+            // about the compression before receiving compressed frames
+            if let Some((algorithm, dictionary)) = negotiated {
+                match shared.compression.compressor(algorithm, dictionary) {
+                    Ok(new) => compressor = Some(new),
+                    Err(e) => warn!("wasn't able to set up {algorithm:?}: {e}"),
+                }
+            }
         }
     }
 }
@@ -2,15 +2,15 @@
 // Copyright (C) 2025 Crypts of the Lost Team
 
 use super::NetworkHandler;
+use crate::{Codec, CodecError};
 use protocol::event::EventKind;
-use rmp_serde::{encode, to_vec};
 use tracing::trace;
 
 impl NetworkHandler {
-    /// Serializes the `Event` struct to a `Vec<u8>`
+    /// Serializes the `Event` struct to a `Vec<u8>` using the codec of the connection
     #[tracing::instrument]
-    pub(super) fn serialize_event(event: &EventKind) -> Result<Vec<u8>, encode::Error> {
+    pub(super) fn serialize_event(codec: Codec, event: &EventKind) -> Result<Vec<u8>, CodecError> {
         trace!("serializing event");
-        to_vec(&event)
+        codec.encode(event)
     }
 }
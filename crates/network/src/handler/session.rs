@@ -0,0 +1,57 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+use super::{NetworkHandler, Shared};
+use crate::Codec;
+use std::future::Future;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tracing::{Instrument, info};
+
+impl NetworkHandler {
+    /// Runs the inbound and outbound side of a connection until one of them
+    /// ends or the connection is closed, independent of the transport the
+    /// client is connected with.
+    ///
+    /// Runs in the `session` span of the client, which the inbound and
+    /// outbound tasks inherit. Removes the client from the handler afterwards.
+    pub(super) async fn run_session<R, W>(
+        shared: Shared,
+        id: u64,
+        codec: Codec,
+        closed: impl Future<Output = ()>,
+        reader: R,
+        writer: W,
+    ) where
+        R: AsyncRead + Unpin + Send + 'static,
+        W: AsyncWrite + Unpin + Send + 'static,
+    {
+        info!("connection {id} uses the {} codec", codec.name());
+
+        let inbound_shared = shared.clone();
+        let mut inbound = tokio::spawn(
+            async move {
+                Self::process_inbound(inbound_shared, reader, id, codec).await;
+            }
+            .in_current_span(),
+        );
+
+        let outbound_shared = shared.clone();
+        let mut outbound = tokio::spawn(
+            async move {
+                Self::process_outbound(outbound_shared, writer, id, codec).await;
+            }
+            .in_current_span(),
+        );
+
+        let reason = tokio::select! {
+            _ = &mut inbound => "inbound ended",
+            _ = &mut outbound => "outbound ended",
+            () = closed => "connection closed",
+        };
+        inbound.abort();
+        outbound.abort();
+
+        info!("cleaning up connection {id} (reason: {reason})");
+        Self::remove_client(&shared, id, 0, b"Connection handler ended");
+    }
+}
@@ -9,9 +9,10 @@ impl NetworkHandler {
     /// Shutdowns the network handler closing all connections and channels.
     pub fn shutdown(&mut self) {
         info!("shutting down network handler");
-        self.connections
+        self.shared
+            .connections
             .iter()
-            .for_each(|item| item.close(VarInt::from_u32(0x100), b"shutting down"));
+            .for_each(|item| item.close(0x100, b"shutting down"));
         if let Some(endpoint) = &self.endpoint {
             endpoint.close(VarInt::from_u32(0x100), b"shutting down");
         }
@@ -4,41 +4,93 @@
 use super::NetworkHandler;
 use crate::error::HandlerError;
 use quinn::Endpoint;
+use std::net::SocketAddr;
 use tracing::{error, info};
 
 impl NetworkHandler {
+    /// Creates the QUIC endpoint and binds it to the socket, without
+    /// accepting connections yet.
+    ///
+    /// Also binds the listener of the WebSocket gateway, when configured.
+    ///
+    /// Returns the address the endpoint is bound to, which has the actual
+    /// port when the configured port is 0.
+    ///
+    /// # Errors
+    /// Returns an error if the endpoint cannot be created or bound to the socket.
+    pub fn bind(&mut self) -> Result<SocketAddr, HandlerError> {
+        let endpoint = Endpoint::server(self.server_config.clone(), self.socket)?;
+        let addr = endpoint.local_addr()?;
+        self.endpoint = Some(endpoint);
+
+        #[cfg(feature = "websocket")]
+        if let Some((socket, _)) = &self.websocket {
+            let listener = std::net::TcpListener::bind(socket)?;
+            listener.set_nonblocking(true)?;
+            self.websocket_listener = Some(listener);
+        }
+
+        Ok(addr)
+    }
+
+    /// The address the WebSocket gateway is bound to, once
+    /// [`bind`](Self::bind) was called
+    #[cfg(feature = "websocket")]
+    #[must_use]
+    pub fn websocket_addr(&self) -> Option<SocketAddr> {
+        self.websocket_listener
+            .as_ref()
+            .and_then(|listener| listener.local_addr().ok())
+    }
+
     /// Starts the network handler and begins to listen for new connections
     ///
-    /// This method initializes the QUIC endpoint, starts accepting connections,
-    /// and spawns tasks for handling outbound messages and connection management.
+    /// This method binds the QUIC endpoint if [`bind`](Self::bind) wasn't
+    /// called yet, starts accepting connections, and spawns tasks for handling
+    /// outbound messages and connection management.
     ///
     /// # Errors
     /// Returns an error if the endpoint cannot be created or bound to the socket.
-    #[tracing::instrument]
+    #[tracing::instrument(skip_all)]
     pub async fn start(&mut self) -> Result<(), HandlerError> {
         info!("starting the networkhandler and listening to connections");
 
-        let endpoint = Endpoint::server(self.server_config.clone(), self.socket)?;
-        self.endpoint = Some(endpoint.clone());
+        if self.endpoint.is_none() {
+            self.bind()?;
+        }
+        let Some(endpoint) = self.endpoint.clone() else {
+            return Ok(());
+        };
 
+        #[cfg(feature = "websocket")]
+        if let (Some(listener), Some((_, tls_config))) =
+            (self.websocket_listener.take(), self.websocket.clone())
+        {
+            let listener = tokio::net::TcpListener::from_std(listener)?;
+            // both listeners stop together when `start` is dropped
+            tokio::select! {
+                () = self.accept_quic(&endpoint) => {}
+                () = Self::accept_websockets(listener, tls_config, self.shared.clone()) => {}
+            }
+            return Ok(());
+        }
+
+        self.accept_quic(&endpoint).await;
+        Ok(())
+    }
+
+    /// Accepts QUIC connections until the endpoint is closed
+    async fn accept_quic(&self, endpoint: &Endpoint) {
         while let Some(incoming) = endpoint.accept().await {
             let Ok(connection) = incoming.await else {
                 error!("Error accepting incoming connection");
+                self.shared.metrics.connection_rejected();
                 continue;
             };
             let addr = connection.remote_address();
             info!("new connection with {addr}");
-            self.add_client(addr, connection.clone());
-
-            let tx = self.inbound_tx.clone();
-            let rx = self.broadcast.subscribe();
-            let connections = self.connections.clone();
 
-            tokio::spawn(async move {
-                Self::handle_connection(connection, connections, tx, rx).await;
-            });
+            tokio::spawn(Self::handle_connection(connection, self.shared.clone()));
         }
-
-        Ok(())
     }
 }
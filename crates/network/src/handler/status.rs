@@ -0,0 +1,77 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+use super::{NetworkHandler, Shared};
+use crate::{Codec, error::HandlerError, frame::MAX_MESSAGE_SIZE};
+use protocol::{PROTOCOL_VERSION, Status};
+use quinn::{Connection, VarInt};
+use std::{io, sync::PoisonError, time::Duration};
+use tracing::info;
+
+/// Information about the server reported as part of its [`Status`]
+#[derive(Debug, Clone, Default)]
+pub struct ServerInfo {
+    /// Message of the day
+    pub motd: String,
+    /// Maximum amount of players on the server
+    pub max_players: u32,
+    /// Version of the server software
+    pub version: String,
+}
+
+impl NetworkHandler {
+    /// How long the server waits for the client to read the status
+    const STATUS_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// The amount of connected clients that joined as a player
+    fn players(shared: &Shared) -> usize {
+        shared
+            .connections
+            .iter()
+            .filter(|client| client.player.is_some())
+            .count()
+    }
+
+    /// The current status of the server
+    fn status(shared: &Shared) -> Status {
+        let info = shared.info.read().unwrap_or_else(PoisonError::into_inner);
+        Status {
+            motd: info.motd.clone(),
+            online: u32::try_from(Self::players(shared)).unwrap_or(u32::MAX),
+            max_players: info.max_players,
+            version: info.version.clone(),
+            protocol: PROTOCOL_VERSION,
+            uptime: shared.started.elapsed().as_secs(),
+        }
+    }
+
+    /// Answers a status query.
+    ///
+    /// The server opens a unidirectional stream, writes a single msgpack
+    /// encoded frame containing the [`Status`] and closes the connection once
+    /// the client has read it. The connection is never added to the clients.
+    #[tracing::instrument(skip_all)]
+    pub(super) async fn handle_status(
+        connection: Connection,
+        shared: &Shared,
+    ) -> Result<(), HandlerError> {
+        info!("answering status query of {}", connection.remote_address());
+
+        let data = Codec::MessagePack.encode(&Self::status(shared))?;
+        let len = u32::try_from(data.len())
+            .ok()
+            .filter(|len| *len <= MAX_MESSAGE_SIZE)
+            .ok_or(HandlerError::MessageTooLarge(data.len()))?;
+
+        let mut send = connection.open_uni().await?;
+        send.write_all(&len.to_be_bytes())
+            .await
+            .map_err(io::Error::from)?;
+        send.write_all(&data).await.map_err(io::Error::from)?;
+        send.finish().map_err(io::Error::from)?;
+
+        let _ = tokio::time::timeout(Self::STATUS_TIMEOUT, send.stopped()).await;
+        connection.close(VarInt::from_u32(0), b"status sent");
+        Ok(())
+    }
+}
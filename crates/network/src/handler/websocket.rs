@@ -0,0 +1,159 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+use super::{NetworkHandler, Shared, client::Transport};
+use crate::{Codec, frame::MAX_MESSAGE_SIZE};
+use futures_util::{SinkExt, StreamExt, future::ready};
+use quinn::rustls;
+use std::{io, net::SocketAddr, sync::Arc, time::Duration};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    time::timeout,
+};
+use tokio_rustls::TlsAcceptor;
+use tokio_tungstenite::{
+    accept_hdr_async_with_config,
+    tungstenite::{
+        Bytes, Message,
+        handshake::server::{Request, Response},
+        http::{HeaderValue, header::SEC_WEBSOCKET_PROTOCOL},
+        protocol::WebSocketConfig,
+    },
+};
+use tokio_util::{
+    io::{CopyToBytes, SinkWriter, StreamReader},
+    sync::CancellationToken,
+};
+use tracing::{Instrument, info, warn};
+
+impl NetworkHandler {
+    /// How long the TLS and the WebSocket handshake may take each
+    const WEBSOCKET_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Accepts WebSocket connections over TLS and feeds them into the same
+    /// pipeline as QUIC connections.
+    ///
+    /// Each binary WebSocket message carries the same length-prefixed frames
+    /// as the QUIC stream does. The codec is picked with the
+    /// `Sec-WebSocket-Protocol` header, see [`Codec::websocket_protocol`].
+    #[tracing::instrument(skip_all)]
+    pub(super) async fn accept_websockets(
+        listener: TcpListener,
+        tls_config: Arc<rustls::ServerConfig>,
+        shared: Shared,
+    ) {
+        info!(
+            "accepting WebSocket connections on {:?}",
+            listener.local_addr()
+        );
+        let acceptor = TlsAcceptor::from(tls_config);
+
+        loop {
+            let (stream, addr) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    warn!("error accepting WebSocket connection: {e}");
+                    continue;
+                }
+            };
+            info!("new WebSocket connection with {addr}");
+
+            tokio::spawn(Self::handle_websocket(
+                acceptor.clone(),
+                stream,
+                addr,
+                shared.clone(),
+            ));
+        }
+    }
+
+    #[tracing::instrument(skip(acceptor, stream, shared))]
+    async fn handle_websocket(
+        acceptor: TlsAcceptor,
+        stream: TcpStream,
+        addr: SocketAddr,
+        shared: Shared,
+    ) {
+        let stream = match timeout(Self::WEBSOCKET_HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await
+        {
+            Ok(Ok(stream)) => stream,
+            Ok(Err(e)) => {
+                warn!("TLS handshake with {addr} failed: {e}");
+                shared.metrics.connection_rejected();
+                return;
+            }
+            Err(_) => {
+                warn!("TLS handshake with {addr} timed out");
+                shared.metrics.connection_rejected();
+                return;
+            }
+        };
+
+        let mut codec = Codec::default();
+        // the error type is dictated by tungstenite
+        #[expect(clippy::result_large_err)]
+        let callback = |request: &Request, mut response: Response| {
+            if let Some(selected) = Self::websocket_codec(request) {
+                codec = selected;
+                response.headers_mut().insert(
+                    SEC_WEBSOCKET_PROTOCOL,
+                    HeaderValue::from_static(selected.websocket_protocol()),
+                );
+            }
+            Ok(response)
+        };
+
+        let frame_size = MAX_MESSAGE_SIZE as usize + 4;
+        let config = WebSocketConfig::default()
+            .max_message_size(Some(frame_size))
+            .max_frame_size(Some(frame_size));
+        let handshake = accept_hdr_async_with_config(stream, callback, Some(config));
+        let websocket = match timeout(Self::WEBSOCKET_HANDSHAKE_TIMEOUT, handshake).await {
+            Ok(Ok(websocket)) => websocket,
+            Ok(Err(e)) => {
+                warn!("WebSocket handshake with {addr} failed: {e}");
+                shared.metrics.connection_rejected();
+                return;
+            }
+            Err(_) => {
+                warn!("WebSocket handshake with {addr} timed out");
+                shared.metrics.connection_rejected();
+                return;
+            }
+        };
+
+        let (sink, stream) = websocket.split();
+        let reader = StreamReader::new(
+            stream
+                .take_while(|message| ready(!matches!(message, Ok(Message::Close(_)))))
+                .filter_map(|message| {
+                    ready(match message {
+                        Ok(Message::Binary(data)) => Some(Ok(data)),
+                        Ok(_) => None,
+                        Err(e) => Some(Err(io::Error::other(e))),
+                    })
+                }),
+        );
+        let writer = SinkWriter::new(CopyToBytes::new(
+            sink.sink_map_err(io::Error::other)
+                .with(|data: Bytes| ready(Ok::<_, io::Error>(Message::Binary(data)))),
+        ));
+
+        let token = CancellationToken::new();
+        let (id, span) = Self::add_client(&shared, Transport::WebSocket(token.clone()), addr);
+        Self::run_session(shared, id, codec, token.cancelled_owned(), reader, writer)
+            .instrument(span)
+            .await;
+    }
+
+    /// Picks the first codec the client offers in `Sec-WebSocket-Protocol`
+    fn websocket_codec(request: &Request) -> Option<Codec> {
+        request
+            .headers()
+            .get_all(SEC_WEBSOCKET_PROTOCOL)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .find_map(|protocol| Codec::from_websocket_protocol(protocol.trim()))
+    }
+}
@@ -7,27 +7,59 @@
 #![expect(clippy::multiple_crate_versions)]
 
 mod bridge;
+pub mod capture;
 mod cert;
+pub mod compression;
+mod control;
 mod error;
 mod handler;
+mod loopback;
+mod metrics;
+mod replay;
 mod setup;
 
+pub use bridge::{CommandSpans, NetworkSet, Traced};
 pub use cert::Certs;
+pub use control::{NetworkAddress, NetworkShutdown, NetworkState, NetworkStatus};
 pub use error::{CertsError, HandlerError};
-pub use handler::NetworkHandler;
+pub use handler::{NetworkHandler, ServerInfo};
+pub use loopback::{Loopback, LoopbackNetwork};
+pub use metrics::{HandlerMetrics, NetworkMetrics};
+pub use replay::{Replay, ReplayNetwork};
+pub use wire::{CaptureError, Codec, CodecError, STATUS_ALPN, frame};
 
-use bevy::app::{Plugin, Startup, Update};
-use bridge::{process_incoming_commands, process_outbound_events};
-use setup::setup;
+use bevy::{
+    app::{Plugin, PreUpdate, Startup},
+    ecs::system::{Commands, Res},
+};
+use bridge::add_bridge_systems;
+use config::{Config, ConfigChanged};
+use setup::{setup, update_status_info};
 
 /// Network plugin which starts the `NetworkHandler` and
 /// the dispatchers.
 #[derive(Debug)]
-pub struct Network;
+pub struct Network {
+    /// Version of the server software, reported in status queries
+    pub version: &'static str,
+}
+
+impl Network {
+    /// Reports the given version of the server software in status queries
+    #[must_use]
+    pub const fn new(version: &'static str) -> Self {
+        Self { version }
+    }
+}
 
 impl Plugin for Network {
     fn build(&self, app: &mut bevy::app::App) {
-        app.add_systems(Startup, setup)
-            .add_systems(Update, (process_incoming_commands, process_outbound_events));
+        let version = self.version;
+        app.add_event::<ConfigChanged>()
+            .add_systems(Startup, move |commands: Commands, config: Res<Config>| {
+                setup(commands, config, version);
+            })
+            .add_systems(PreUpdate, update_status_info);
+        add_bridge_systems(app);
     }
 }
@@ -0,0 +1,265 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # Loopback
+//! A network plugin without sockets or certificates, for tests and headless
+//! simulations. Commands are injected as a player and the events that would
+//! be sent to that player are collected in memory.
+
+use crate::bridge::{CommandReceiver, EventSender, Traced, add_bridge_systems};
+use bevy::{
+    app::{App, Plugin},
+    ecs::resource::Resource,
+};
+use protocol::{Stamped, Targetable, command::CommandKind, event::EventKind};
+use std::collections::HashMap;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+use tracing::{Span, info_span};
+
+/// Network plugin that replaces [`Network`](crate::Network) with in-memory
+/// channels. Use the [`Loopback`] resource to talk to the game.
+#[derive(Debug)]
+pub struct LoopbackNetwork;
+
+impl Plugin for LoopbackNetwork {
+    fn build(&self, app: &mut App) {
+        let (inbound_tx, inbound_rx) = unbounded_channel::<Traced<CommandKind>>();
+        let (outbound_tx, outbound_rx) = unbounded_channel::<Stamped<EventKind>>();
+
+        app.insert_resource(CommandReceiver { rx: inbound_rx })
+            .insert_resource(EventSender { tx: outbound_tx })
+            .insert_resource(Loopback {
+                inbound_tx,
+                outbound_rx,
+                players: HashMap::new(),
+                spans: HashMap::new(),
+            });
+        add_bridge_systems(app);
+    }
+}
+
+/// Test API of the [`LoopbackNetwork`], connects players and sends commands
+/// on their behalf.
+///
+/// Every player acts like its own connection, with its uuid as the
+/// connection id, and has a `session` span like a network session. Events are
+/// delivered to the connected players that are a recipient according to
+/// their [`Target`](protocol::Target).
+#[derive(Debug, Resource)]
+pub struct Loopback {
+    inbound_tx: UnboundedSender<Traced<CommandKind>>,
+    outbound_rx: UnboundedReceiver<Stamped<EventKind>>,
+    /// Events received by every connected player, not yet taken
+    players: HashMap<u64, Vec<EventKind>>,
+    /// The session span of every connected player
+    spans: HashMap<u64, Span>,
+}
+
+impl Loopback {
+    /// Sends a command as the given player, connecting it if it isn't yet.
+    ///
+    /// A `Join` is filled in like the network handler would, with the player
+    /// as the connection and without address or compression.
+    pub fn send(&mut self, player: u64, command: impl Into<CommandKind>) {
+        self.collect();
+        self.players.entry(player).or_default();
+
+        let mut command = command.into();
+        if let CommandKind::Join(join) = &mut command {
+            join.ip = None;
+            join.connection = Some(player);
+            join.compression = None;
+            join.dictionary = None;
+        }
+        let span = self
+            .spans
+            .entry(player)
+            .or_insert_with(|| info_span!(parent: None, "session", session = player, uuid = player))
+            .clone();
+        let _ = self.inbound_tx.send(Traced {
+            span,
+            message: command,
+        });
+    }
+
+    /// Takes the events the player received since the last call
+    #[must_use]
+    pub fn events(&mut self, player: u64) -> Vec<EventKind> {
+        self.collect();
+        self.players
+            .get_mut(&player)
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    /// Disconnects the player, it won't receive events anymore
+    pub fn disconnect(&mut self, player: u64) {
+        self.collect();
+        self.players.remove(&player);
+        self.spans.remove(&player);
+    }
+
+    /// Delivers the events sent by the game to the connected players
+    fn collect(&mut self) {
+        while let Ok(Stamped { message: event, .. }) = self.outbound_rx.try_recv() {
+            for (player, events) in &mut self.players {
+                if event.is_recipient(player) {
+                    events.push(event.clone());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CommandSpans, NetworkSet};
+    use bevy::{
+        app::{PostUpdate, PreUpdate, Update},
+        ecs::event::{EventReader, EventRegistry, EventWriter, Events, ShouldUpdateEvents},
+        ecs::schedule::IntoScheduleConfigs,
+    };
+    use protocol::{
+        Protocol,
+        command::join::Join,
+        event::{JoinAccept, PlayerJoined},
+    };
+
+    fn accept_joins(
+        mut joins: EventReader<Join>,
+        mut accepts: EventWriter<JoinAccept>,
+        mut joined: EventWriter<PlayerJoined>,
+    ) {
+        for join in joins.read() {
+            accepts.write(JoinAccept {
+                connection: join.connection.unwrap_or_default(),
+                uuid: join.uuid,
+                compression: join.compression,
+                dictionary: join.dictionary,
+            });
+            joined.write(PlayerJoined {});
+        }
+    }
+
+    fn join(uuid: u64) -> Join {
+        Join {
+            uuid,
+            hash: 0,
+            ip: None,
+            connection: None,
+            compression: None,
+            dictionary: None,
+        }
+    }
+
+    #[test]
+    fn events_reach_their_targets() {
+        let mut app = App::new();
+        app.add_plugins((Protocol, LoopbackNetwork))
+            .add_systems(Update, accept_joins);
+
+        let mut loopback = app.world_mut().resource_mut::<Loopback>();
+        loopback.send(1, join(1));
+        loopback.send(2, join(2));
+        // received in `PreUpdate`, answered in `Update`, sent in `PostUpdate`
+        app.update();
+
+        let mut loopback = app.world_mut().resource_mut::<Loopback>();
+        let events = loopback.events(1);
+        assert!(events.contains(&EventKind::JoinAccept(JoinAccept {
+            connection: 1,
+            uuid: 1,
+            compression: None,
+            dictionary: None,
+        })));
+        assert!(
+            !events
+                .iter()
+                .any(|event| matches!(event, EventKind::JoinAccept(accept) if accept.uuid == 2))
+        );
+        assert_eq!(
+            events
+                .iter()
+                .filter(|event| matches!(event, EventKind::PlayerJoined(_)))
+                .count(),
+            2
+        );
+        assert!(loopback.events(1).is_empty());
+    }
+
+    #[test]
+    fn gameplay_runs_between_receive_and_send() {
+        let mut app = App::new();
+        app.add_plugins((Protocol, LoopbackNetwork))
+            .add_systems(PreUpdate, accept_joins.after(NetworkSet::Receive))
+            .add_systems(
+                PostUpdate,
+                (|mut joined: EventWriter<PlayerJoined>| {
+                    joined.write(PlayerJoined {});
+                })
+                .before(NetworkSet::Send),
+            );
+
+        app.world_mut().resource_mut::<Loopback>().send(1, join(1));
+        app.update();
+
+        let events = app.world_mut().resource_mut::<Loopback>().events(1);
+        let names: Vec<_> = events.iter().map(EventKind::name).collect();
+        assert_eq!(names, ["JoinAccept", "PlayerJoined", "PlayerJoined"]);
+    }
+
+    #[test]
+    fn commands_carry_the_session_span() {
+        let _subscriber = tracing::subscriber::set_default(tracing_subscriber::registry());
+        let mut app = App::new();
+        app.add_plugins((Protocol, LoopbackNetwork));
+
+        app.world_mut().resource_mut::<Loopback>().send(7, join(7));
+        app.update();
+
+        let events = app.world().resource::<Events<Join>>();
+        let spans = app.world().resource::<CommandSpans>();
+        let ids: Vec<_> = events
+            .get_cursor()
+            .read_with_id(events)
+            .map(|(_, id)| id)
+            .collect();
+        assert_eq!(ids.len(), 1);
+        let span = spans.span(ids[0]);
+        assert_eq!(
+            span.metadata().map(tracing::Metadata::name),
+            Some("session")
+        );
+        assert!(span.field("uuid").is_some());
+    }
+
+    #[test]
+    fn spans_are_kept_as_long_as_the_events() {
+        let _subscriber = tracing::subscriber::set_default(tracing_subscriber::registry());
+        let mut app = App::new();
+        app.add_plugins((Protocol, LoopbackNetwork));
+        // like with a fixed timestep, when no tick ran since the last update
+        app.world_mut()
+            .resource_mut::<EventRegistry>()
+            .should_update = ShouldUpdateEvents::Waiting;
+
+        app.world_mut().resource_mut::<Loopback>().send(7, join(7));
+        for _ in 0..3 {
+            app.update();
+        }
+
+        let events = app.world().resource::<Events<Join>>();
+        let spans = app.world().resource::<CommandSpans>();
+        let ids: Vec<_> = events
+            .get_cursor()
+            .read_with_id(events)
+            .map(|(_, id)| id)
+            .collect();
+        assert_eq!(ids.len(), 1);
+        assert_eq!(
+            spans.span(ids[0]).metadata().map(tracing::Metadata::name),
+            Some("session")
+        );
+    }
+}
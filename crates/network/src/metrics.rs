@@ -0,0 +1,222 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # Metrics
+//! Exposes the counters of the `NetworkHandler` to the bevy world.
+
+use crate::compression::CompressionMetrics;
+use bevy::ecs::resource::Resource;
+use protocol::{command::CommandKind, event::EventKind};
+use std::{
+    collections::BTreeMap,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+/// Counters collected by the network, inserted as a resource by the `Network` plugin
+#[derive(Debug, Clone, Resource)]
+pub struct NetworkMetrics {
+    /// Bandwidth saved by compressing outbound frames
+    pub compression: Arc<CompressionMetrics>,
+    /// Connections, messages and queues of the network handler
+    pub handler: Arc<HandlerMetrics>,
+}
+
+/// Counters about the connections and the messages sent over them
+#[derive(Debug)]
+pub struct HandlerMetrics {
+    connections: AtomicU64,
+    accepted_connections: AtomicU64,
+    rejected_connections: AtomicU64,
+    joins: AtomicU64,
+    inbound: MessageCounters,
+    outbound: MessageCounters,
+    deserialize_failures: AtomicU64,
+    serialize_failures: AtomicU64,
+    inbound_queue: AtomicU64,
+    outbound_queue: AtomicU64,
+    broadcast_queue: AtomicU64,
+    lagged_events: AtomicU64,
+}
+
+impl Default for HandlerMetrics {
+    fn default() -> Self {
+        Self {
+            connections: AtomicU64::default(),
+            accepted_connections: AtomicU64::default(),
+            rejected_connections: AtomicU64::default(),
+            joins: AtomicU64::default(),
+            inbound: MessageCounters::new(CommandKind::NAMES),
+            outbound: MessageCounters::new(EventKind::NAMES),
+            deserialize_failures: AtomicU64::default(),
+            serialize_failures: AtomicU64::default(),
+            inbound_queue: AtomicU64::default(),
+            outbound_queue: AtomicU64::default(),
+            broadcast_queue: AtomicU64::default(),
+            lagged_events: AtomicU64::default(),
+        }
+    }
+}
+
+impl HandlerMetrics {
+    pub(crate) fn connection_opened(&self) {
+        self.connections.fetch_add(1, Ordering::Relaxed);
+        self.accepted_connections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn connection_closed(&self) {
+        self.connections.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn connection_rejected(&self) {
+        self.rejected_connections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn command_received(&self, command: &CommandKind, bytes: usize) {
+        if matches!(command, CommandKind::Join(_)) {
+            self.joins.fetch_add(1, Ordering::Relaxed);
+        }
+        self.inbound.record(command.name(), bytes);
+    }
+
+    pub(crate) fn event_sent(&self, event: &EventKind, bytes: usize) {
+        self.outbound.record(event.name(), bytes);
+    }
+
+    pub(crate) fn deserialize_failed(&self) {
+        self.deserialize_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn serialize_failed(&self) {
+        self.serialize_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn set_inbound_queue(&self, depth: usize) {
+        self.inbound_queue.store(depth as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn set_outbound_queue(&self, depth: usize) {
+        self.outbound_queue.store(depth as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn set_broadcast_queue(&self, depth: usize) {
+        self.broadcast_queue.store(depth as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn lagged(&self, skipped: u64) {
+        self.lagged_events.fetch_add(skipped, Ordering::Relaxed);
+    }
+
+    /// Amount of open connections, status queries excluded
+    #[must_use]
+    pub fn connections(&self) -> u64 {
+        self.connections.load(Ordering::Relaxed)
+    }
+
+    /// Amount of connections accepted since the start
+    #[must_use]
+    pub fn accepted_connections(&self) -> u64 {
+        self.accepted_connections.load(Ordering::Relaxed)
+    }
+
+    /// Amount of connections that failed the handshake or didn't open a stream
+    #[must_use]
+    pub fn rejected_connections(&self) -> u64 {
+        self.rejected_connections.load(Ordering::Relaxed)
+    }
+
+    /// Amount of `Join` commands received
+    #[must_use]
+    pub fn joins(&self) -> u64 {
+        self.joins.load(Ordering::Relaxed)
+    }
+
+    /// Received commands and their size in bytes, by type
+    #[must_use]
+    pub fn inbound(&self) -> BTreeMap<&'static str, (u64, u64)> {
+        self.inbound.snapshot()
+    }
+
+    /// Sent events and their size in bytes, by type
+    #[must_use]
+    pub fn outbound(&self) -> BTreeMap<&'static str, (u64, u64)> {
+        self.outbound.snapshot()
+    }
+
+    /// Amount of received frames that couldn't be decoded
+    #[must_use]
+    pub fn deserialize_failures(&self) -> u64 {
+        self.deserialize_failures.load(Ordering::Relaxed)
+    }
+
+    /// Amount of events that couldn't be encoded
+    #[must_use]
+    pub fn serialize_failures(&self) -> u64 {
+        self.serialize_failures.load(Ordering::Relaxed)
+    }
+
+    /// Commands left for the next tick after the last one
+    #[must_use]
+    pub fn inbound_queue(&self) -> u64 {
+        self.inbound_queue.load(Ordering::Relaxed)
+    }
+
+    /// Events waiting to be fanned out to the connections
+    #[must_use]
+    pub fn outbound_queue(&self) -> u64 {
+        self.outbound_queue.load(Ordering::Relaxed)
+    }
+
+    /// Events in the broadcast channel not yet read by every connection
+    #[must_use]
+    pub fn broadcast_queue(&self) -> u64 {
+        self.broadcast_queue.load(Ordering::Relaxed)
+    }
+
+    /// Events skipped by connections that fell behind the broadcast channel
+    #[must_use]
+    pub fn lagged_events(&self) -> u64 {
+        self.lagged_events.load(Ordering::Relaxed)
+    }
+}
+
+/// Amount and size of messages, by the name of their type
+#[derive(Debug)]
+struct MessageCounters {
+    counters: BTreeMap<&'static str, (AtomicU64, AtomicU64)>,
+}
+
+impl MessageCounters {
+    fn new(names: &[&'static str]) -> Self {
+        Self {
+            counters: names
+                .iter()
+                .map(|name| (*name, (AtomicU64::default(), AtomicU64::default())))
+                .collect(),
+        }
+    }
+
+    fn record(&self, name: &str, bytes: usize) {
+        if let Some((messages, total)) = self.counters.get(name) {
+            messages.fetch_add(1, Ordering::Relaxed);
+            total.fetch_add(bytes as u64, Ordering::Relaxed);
+        }
+    }
+
+    fn snapshot(&self) -> BTreeMap<&'static str, (u64, u64)> {
+        self.counters
+            .iter()
+            .map(|(name, (messages, bytes))| {
+                (
+                    *name,
+                    (
+                        messages.load(Ordering::Relaxed),
+                        bytes.load(Ordering::Relaxed),
+                    ),
+                )
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,225 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # Replay
+//! Feeds the commands of a capture back into a headless server, through the
+//! [`LoopbackNetwork`], at the time they were recorded.
+
+use crate::{
+    Loopback, LoopbackNetwork, NetworkSet,
+    capture::{Message, Record},
+};
+use bevy::{
+    app::{App, Plugin, PreUpdate},
+    ecs::{
+        resource::Resource,
+        schedule::IntoScheduleConfigs,
+        system::{Local, ResMut},
+    },
+};
+use protocol::{command::CommandKind, event::EventKind};
+use std::{
+    collections::{HashMap, VecDeque},
+    time::{Duration, Instant},
+};
+
+/// Network plugin that replays a capture, in place of [`Network`](crate::Network).
+///
+/// Adds the [`LoopbackNetwork`], every recorded session becomes a loopback
+/// player with the uuid it joined with as its id. The [`Replay`] resource
+/// tracks the progress and the events the sessions originally received.
+#[derive(Debug)]
+pub struct ReplayNetwork {
+    records: Vec<Record>,
+}
+
+impl ReplayNetwork {
+    /// Replays the given records, see [`capture::read_file`](crate::capture::read_file)
+    #[must_use]
+    pub const fn new(records: Vec<Record>) -> Self {
+        Self { records }
+    }
+}
+
+impl Plugin for ReplayNetwork {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(LoopbackNetwork)
+            .insert_resource(Replay::new(self.records.clone()))
+            .add_systems(PreUpdate, replay_commands.before(NetworkSet::Receive));
+    }
+}
+
+/// Progress of a replayed capture
+#[derive(Debug, Resource)]
+pub struct Replay {
+    /// Commands not yet sent
+    pending: VecDeque<Record>,
+    /// Every recorded event, to compare the replay against
+    recorded: Vec<Record>,
+    /// The uuid every session joined with
+    players: HashMap<u64, u64>,
+    /// Position in the capture
+    elapsed: Duration,
+}
+
+impl Replay {
+    /// Creates a replay of the given records
+    #[must_use]
+    pub fn new(records: Vec<Record>) -> Self {
+        let (pending, recorded): (Vec<_>, _) = records
+            .into_iter()
+            .partition(|record| matches!(record.message, Message::Inbound(_)));
+        Self {
+            pending: pending.into(),
+            recorded,
+            players: HashMap::new(),
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    /// Moves the replay forward and sends the commands that were recorded
+    /// in that time, in their original order.
+    ///
+    /// Commands are sent as the player the session joined as, events are
+    /// targeted at uuids and not at sessions.
+    pub fn advance(&mut self, by: Duration, loopback: &mut Loopback) {
+        self.elapsed += by;
+        while self
+            .pending
+            .front()
+            .is_some_and(|record| record.elapsed() <= self.elapsed)
+        {
+            let Some(Record {
+                session,
+                message: Message::Inbound(command),
+                ..
+            }) = self.pending.pop_front()
+            else {
+                continue;
+            };
+            if let CommandKind::Join(join) = &command {
+                self.players.insert(session, join.uuid);
+            }
+            let player = self.player(session).unwrap_or(session);
+            loopback.send(player, command);
+        }
+    }
+
+    /// Whether every command has been sent
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// The loopback player of the session, once its `Join` has been sent
+    #[must_use]
+    pub fn player(&self, session: u64) -> Option<u64> {
+        self.players.get(&session).copied()
+    }
+
+    /// Position in the capture
+    #[must_use]
+    pub const fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// The events the session received while it was recorded
+    pub fn recorded_events(&self, session: u64) -> impl Iterator<Item = &EventKind> {
+        self.recorded
+            .iter()
+            .filter_map(move |record| match &record.message {
+                Message::Outbound(event) if record.session == session => Some(&event.message),
+                Message::Outbound(_) | Message::Inbound(_) => None,
+            })
+    }
+}
+
+/// Advances the replay by the real time between updates
+fn replay_commands(
+    mut replay: ResMut<Replay>,
+    mut loopback: ResMut<Loopback>,
+    mut last: Local<Option<Instant>>,
+) {
+    let now = Instant::now();
+    let by = last.map_or(Duration::ZERO, |last| now - last);
+    *last = Some(now);
+    replay.advance(by, &mut loopback);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::{
+        event::{EventReader, EventWriter},
+        world::Mut,
+    };
+    use protocol::{Protocol, command::join::Join, event::JoinAccept};
+
+    fn join(at: u64, session: u64, uuid: u64) -> Record {
+        Record {
+            at,
+            session,
+            message: Message::Inbound(CommandKind::Join(Join {
+                uuid,
+                hash: 0,
+                ip: None,
+                connection: Some(session),
+                compression: None,
+                dictionary: None,
+            })),
+        }
+    }
+
+    #[test]
+    fn commands_are_sent_at_their_time() {
+        let mut app = App::new();
+        app.add_plugins((
+            Protocol,
+            ReplayNetwork::new(vec![join(0, 1, 1), join(2_000_000, 2, 2)]),
+        ));
+        app.update();
+
+        app.world_mut()
+            .resource_scope(|world, mut replay: Mut<Replay>| {
+                assert_eq!(replay.pending.len(), 1);
+
+                let mut loopback = world.resource_mut::<Loopback>();
+                replay.advance(Duration::from_secs(1), &mut loopback);
+                assert!(!replay.is_finished());
+                replay.advance(Duration::from_secs(1), &mut loopback);
+                assert!(replay.is_finished());
+            });
+    }
+
+    #[test]
+    fn sessions_play_as_the_uuid_they_joined_with() {
+        let mut app = App::new();
+        app.add_plugins((Protocol, ReplayNetwork::new(vec![join(0, 3, 42)])))
+            .add_systems(
+                bevy::app::Update,
+                |mut joins: EventReader<Join>, mut accepts: EventWriter<JoinAccept>| {
+                    for join in joins.read() {
+                        accepts.write(JoinAccept {
+                            connection: join.connection.unwrap_or_default(),
+                            uuid: join.uuid,
+                            compression: None,
+                            dictionary: None,
+                        });
+                    }
+                },
+            );
+        app.update();
+
+        assert_eq!(app.world().resource::<Replay>().player(3), Some(42));
+        let events = app.world_mut().resource_mut::<Loopback>().events(42);
+        assert_eq!(
+            events,
+            [EventKind::JoinAccept(JoinAccept {
+                connection: 42,
+                uuid: 42,
+                compression: None,
+                dictionary: None,
+            })]
+        );
+    }
+}
@@ -6,43 +6,164 @@
 //! new connections.
 
 use crate::{
-    Certs, NetworkHandler,
-    bridge::{CommandReceiver, EventSender},
+    Certs, Codec, NetworkHandler, NetworkMetrics, STATUS_ALPN, ServerInfo,
+    bridge::{CommandReceiver, EventSender, Traced},
+    capture::Recorder,
+    compression::CompressionContext,
+    control::{NetworkAddress, NetworkShutdown, NetworkState, NetworkStatus},
 };
-use bevy::ecs::system::{Commands, Res};
-use config::Config;
-use protocol::{command::CommandKind, event::EventKind};
+use bevy::{
+    app::AppExit,
+    ecs::{
+        event::EventReader,
+        resource::Resource,
+        system::{Commands, Res},
+    },
+};
+use config::{Config, ConfigChanged};
+use protocol::{Stamped, command::CommandKind, event::EventKind};
+use std::sync::{Arc, PoisonError, RwLock};
+#[cfg(not(feature = "websocket"))]
+use tracing::warn;
 use tracing::{error, info};
 
 #[expect(clippy::expect_used)]
 #[tracing::instrument(skip_all)]
-pub fn setup(mut commands: Commands, config: Res<Config>) {
+pub fn setup(mut commands: Commands, config: Res<Config>, version: &str) {
     info!("Setting up network");
 
-    let (inbound_tx, inbound_rx) = tokio::sync::mpsc::unbounded_channel::<CommandKind>();
-    let (outbound_tx, outbound_rx) = tokio::sync::mpsc::unbounded_channel::<EventKind>();
+    let (inbound_tx, inbound_rx) = tokio::sync::mpsc::unbounded_channel::<Traced<CommandKind>>();
+    let (outbound_tx, outbound_rx) = tokio::sync::mpsc::unbounded_channel::<Stamped<EventKind>>();
 
     let certs = Certs::read_from_file(&config.network.certs, &config.network.key)
         .expect("A TLS certificate and private key (self- or externally-signed) are required to start a server.");
 
+    #[cfg(feature = "websocket")]
+    let websocket = config.network.websocket.map(|socket| {
+        let tls_config = certs
+            .create_tls_config(vec![b"http/1.1".to_vec()])
+            .expect("Wasn't able to create the TLS config for WebSockets");
+        (socket, Arc::new(tls_config))
+    });
+    #[cfg(not(feature = "websocket"))]
+    if config.network.websocket.is_some() {
+        warn!(
+            "`network.websocket` is set, but the server was built without the `websocket` feature"
+        );
+    }
+
+    let mut alpn_protocols = Codec::alpn_protocols();
+    alpn_protocols.push(STATUS_ALPN.to_vec());
     let server_config = certs
-        .create_server_config()
+        .create_server_config(alpn_protocols)
         .expect("Wasn't able to create the ServerConfig");
 
+    let compression = CompressionContext::from_config(&config.network.compression)
+        .expect("Wasn't able to read the compression dictionary");
+
+    let compression_metrics = compression.metrics();
+
+    let info = ServerInfo {
+        motd: config.motd.clone(),
+        max_players: config.max_players,
+        version: version.to_owned(),
+    };
+
     let mut handler = NetworkHandler::new(
         config.network.socket,
         server_config,
         outbound_rx,
         inbound_tx,
+        compression,
+        info,
     );
+    #[cfg(feature = "websocket")]
+    if let Some((socket, tls_config)) = websocket {
+        handler = handler.with_websocket(socket, tls_config);
+    }
 
-    tokio::spawn(async move {
-        if let Err(e) = handler.start().await {
-            error!("Failed to start network handler: {e}");
-            std::process::exit(1);
+    if let Some(path) = &config.network.record {
+        match Recorder::create(path) {
+            Ok(recorder) => handler = handler.with_recorder(recorder),
+            Err(e) => error!("Wasn't able to record sessions to {}: {e}", path.display()),
         }
-    });
+    }
 
+    commands.insert_resource(StatusInfo(handler.info()));
     commands.insert_resource(CommandReceiver { rx: inbound_rx });
     commands.insert_resource(EventSender { tx: outbound_tx });
+    commands.insert_resource(NetworkMetrics {
+        compression: compression_metrics,
+        handler: handler.metrics(),
+    });
+
+    // the health endpoint reports a failing handler, so supervisors can restart the server
+    let status = NetworkStatus::default();
+    commands.insert_resource(status.clone());
+
+    // a server that can't accept connections is of no use, exit with an error
+    let addr = match handler.bind() {
+        Ok(addr) => addr,
+        Err(e) => {
+            error!(
+                "Failed to bind network handler to {}: {e}, exiting",
+                config.network.socket
+            );
+            status.set(NetworkState::Stopped(format!("failed to bind: {e}")));
+            commands.send_event(AppExit::error());
+            return;
+        }
+    };
+    info!("network handler bound to {addr}");
+    #[cfg(feature = "websocket")]
+    let websocket_addr = handler.websocket_addr();
+    #[cfg(not(feature = "websocket"))]
+    let websocket_addr = None;
+    status.set(NetworkState::Running);
+
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel();
+    tokio::spawn(async move {
+        let reason = tokio::select! {
+            result = handler.start() => match result {
+                Ok(()) => "the endpoint was closed".to_owned(),
+                Err(e) => {
+                    error!("Network handler failed: {e}");
+                    format!("failed: {e}")
+                }
+            },
+            _ = shutdown_rx => {
+                info!("stopping the network handler");
+                "shut down".to_owned()
+            }
+        };
+        status.set(NetworkState::Stopped(reason));
+    });
+
+    commands.insert_resource(NetworkAddress {
+        socket: addr,
+        websocket: websocket_addr,
+    });
+    commands.insert_resource(NetworkShutdown::new(shutdown_tx));
+}
+
+/// The information the handler reports in status queries
+#[derive(Debug, Resource)]
+pub struct StatusInfo(Arc<RwLock<ServerInfo>>);
+
+/// Reports the reloaded MOTD and player limit in status queries
+pub fn update_status_info(
+    mut changes: EventReader<ConfigChanged>,
+    config: Res<Config>,
+    info: Option<Res<StatusInfo>>,
+) {
+    let Some(info) = info else {
+        changes.clear();
+        return;
+    };
+    if changes.read().count() == 0 {
+        return;
+    }
+    let mut info = info.0.write().unwrap_or_else(PoisonError::into_inner);
+    info.motd.clone_from(&config.motd);
+    info.max_players = config.max_players;
 }
@@ -0,0 +1,324 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # Harness
+//! Runs the real `Protocol` and `Network` plugins on an ephemeral port with
+//! generated certificates, on a thread of their own.
+
+use bevy::{
+    app::{App, AppExit, Update},
+    ecs::event::{EventReader, EventWriter},
+};
+use client::{Client, ClientBuilder};
+use config::{Config, config::network::NetworkConfig};
+use network::{Codec, Network, NetworkAddress, NetworkShutdown, frame::MAX_MESSAGE_SIZE};
+use protocol::{
+    Protocol, Status,
+    command::{CommandKind, join::Join},
+    event::{EventKind, JoinAccept, PlayerJoined},
+};
+use quinn::{
+    Connection, Endpoint, RecvStream, SendStream,
+    crypto::rustls::QuicClientConfig,
+    rustls::{self, RootCertStore, crypto::ring, version::TLS13},
+};
+use rustls_pki_types::CertificateDer;
+use std::{
+    error::Error,
+    future::Future,
+    net::SocketAddr,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc,
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+use tempfile::TempDir;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+pub type TestResult<T = ()> = Result<T, Box<dyn Error + Send + Sync>>;
+
+/// How long a test waits for the server before failing
+pub const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Version of the server software the test server reports
+pub const VERSION: &str = "0.0.0-test";
+
+/// The default test config, with certificates generated into the directory
+fn test_config(dir: &TempDir) -> TestResult<(Config, CertificateDer<'static>)> {
+    let generated = rcgen::generate_simple_self_signed(vec!["localhost".to_owned()])?;
+    let certs = dir.path().join("certs.pem");
+    let key = dir.path().join("key.pem");
+    std::fs::write(&certs, generated.cert.pem())?;
+    std::fs::write(&key, generated.signing_key.serialize_pem())?;
+
+    let config = Config {
+        network: NetworkConfig {
+            socket: (std::net::Ipv4Addr::LOCALHOST, 0).into(),
+            certs,
+            key,
+            ..NetworkConfig::default()
+        },
+        ..Config::default()
+    };
+    Ok((config, generated.cert.der().clone()))
+}
+
+/// Fails when the future doesn't finish within [`TIMEOUT`]
+pub async fn timeout<T>(future: impl Future<Output = T>) -> TestResult<T> {
+    Ok(tokio::time::timeout(TIMEOUT, future).await?)
+}
+
+/// Polls the condition until it holds, failing after [`TIMEOUT`]
+pub async fn eventually<F, Fut>(mut condition: F) -> TestResult
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = TestResult<bool>>,
+{
+    timeout(async {
+        while !condition().await? {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        Ok(())
+    })
+    .await?
+}
+
+/// Minimal game logic: accepts every join and tells everyone about it
+///
+/// The `JoinAccept` is always sent first, so the connection already knows
+/// its player when the broadcast arrives.
+fn accept_joins(
+    mut joins: EventReader<Join>,
+    mut accepts: EventWriter<JoinAccept>,
+    mut joined: EventWriter<PlayerJoined>,
+) {
+    for join in joins.read() {
+        let Some(connection) = join.connection else {
+            continue;
+        };
+        accepts.write(JoinAccept {
+            connection,
+            uuid: join.uuid,
+            compression: join.compression,
+            dictionary: join.dictionary,
+        });
+        joined.write(PlayerJoined {});
+    }
+}
+
+/// A server running the real network plugin
+pub struct TestServer {
+    pub addr: SocketAddr,
+    /// Address of the WebSocket gateway, when it is enabled in the config
+    #[cfg(feature = "websocket")]
+    pub websocket: Option<SocketAddr>,
+    cert: CertificateDer<'static>,
+    stop: Arc<AtomicBool>,
+    stop_network: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+    _dir: TempDir,
+}
+
+impl TestServer {
+    pub fn start() -> TestResult<Self> {
+        Self::start_with(|_| {})
+    }
+
+    /// Starts a server after changing the default test config
+    pub fn start_with(configure: impl FnOnce(&mut Config)) -> TestResult<Self> {
+        let dir = tempfile::tempdir()?;
+        let (mut config, cert) = test_config(&dir)?;
+        configure(&mut config);
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_network = Arc::new(AtomicBool::new(false));
+        let (addr_tx, addr_rx) = mpsc::channel();
+        let thread = std::thread::spawn({
+            let stop = stop.clone();
+            let stop_network = stop_network.clone();
+            move || Self::run(config, &stop, &stop_network, &addr_tx)
+        });
+        let addr: NetworkAddress = addr_rx.recv_timeout(TIMEOUT)?;
+
+        Ok(Self {
+            addr: addr.socket,
+            #[cfg(feature = "websocket")]
+            websocket: addr.websocket,
+            cert,
+            stop,
+            stop_network,
+            thread: Some(thread),
+            _dir: dir,
+        })
+    }
+
+    /// Runs the startup of a server with the changed test config, and
+    /// returns the exit it requested
+    pub fn exit_with(configure: impl FnOnce(&mut Config)) -> TestResult<Option<AppExit>> {
+        let dir = tempfile::tempdir()?;
+        let (mut config, _) = test_config(&dir)?;
+        configure(&mut config);
+
+        let runtime = tokio::runtime::Runtime::new()?;
+        let _guard = runtime.enter();
+        let mut app = App::new();
+        app.add_plugins((Protocol, Network::new(VERSION)))
+            .insert_resource(config);
+        app.update();
+        Ok(app.should_exit())
+    }
+
+    /// Ticks the app until the server is stopped
+    fn run(
+        config: Config,
+        stop: &AtomicBool,
+        stop_network: &AtomicBool,
+        addr_tx: &mpsc::Sender<NetworkAddress>,
+    ) {
+        let Ok(runtime) = tokio::runtime::Runtime::new() else {
+            return;
+        };
+
+        runtime.block_on(async {
+            let mut app = App::new();
+            app.add_plugins((Protocol, Network::new(VERSION)))
+                .insert_resource(config)
+                .add_systems(Update, accept_joins);
+            app.update();
+
+            let Some(addr) = app.world().get_resource::<NetworkAddress>() else {
+                return;
+            };
+            let _ = addr_tx.send(*addr);
+
+            while !stop.load(Ordering::Relaxed) {
+                if stop_network.swap(false, Ordering::Relaxed) {
+                    app.world_mut().resource_mut::<NetworkShutdown>().shutdown();
+                }
+                app.update();
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+
+            // dropping the app stops the network handler, give it some
+            // time to close the connections
+            drop(app);
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        });
+    }
+
+    /// Stops the network handler with the `NetworkShutdown` resource, while
+    /// the app keeps running
+    pub fn stop_network(&self) {
+        self.stop_network.store(true, Ordering::Relaxed);
+    }
+
+    /// Stops the server and waits until it closed all connections
+    pub fn shutdown(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+
+    /// A client builder trusting the certificate of this server
+    pub fn builder(&self) -> TestResult<ClientBuilder> {
+        Ok(ClientBuilder::new().with_certificate(self.cert.clone())?)
+    }
+
+    /// Connects a client using the client SDK
+    pub async fn connect(&self) -> TestResult<Client> {
+        Ok(timeout(self.builder()?.connect(self.addr, "localhost")).await??)
+    }
+
+    /// Queries the status of the server with the `cotl/status` ALPN
+    pub async fn status(&self) -> TestResult<Status> {
+        Ok(timeout(self.builder()?.status(self.addr, "localhost")).await??)
+    }
+
+    /// The amount of joined players, according to the status of the server
+    pub async fn online(&self) -> TestResult<u32> {
+        Ok(self.status().await?.online)
+    }
+
+    /// A TLS config trusting the certificate of this server
+    pub fn tls_config(&self, alpn_protocols: Vec<Vec<u8>>) -> TestResult<rustls::ClientConfig> {
+        let mut roots = RootCertStore::empty();
+        roots.add(self.cert.clone())?;
+        let mut crypto =
+            rustls::ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+                .with_protocol_versions(&[&TLS13])?
+                .with_root_certificates(roots)
+                .with_no_client_auth();
+        crypto.alpn_protocols = alpn_protocols;
+        Ok(crypto)
+    }
+
+    /// Connects a plain quinn client, to send frames the SDK won't send
+    pub async fn connect_raw(&self) -> TestResult<RawClient> {
+        let crypto = self.tls_config(vec![Codec::MessagePack.alpn().to_vec()])?;
+
+        let mut endpoint = Endpoint::client((std::net::Ipv4Addr::LOCALHOST, 0).into())?;
+        endpoint.set_default_client_config(quinn::ClientConfig::new(Arc::new(
+            QuicClientConfig::try_from(crypto)?,
+        )));
+        let connection = timeout(endpoint.connect(self.addr, "localhost")?).await??;
+        let (send, recv) = connection.open_bi().await?;
+
+        Ok(RawClient {
+            endpoint,
+            connection,
+            send,
+            recv,
+        })
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// A plain quinn connection speaking msgpack
+pub struct RawClient {
+    pub endpoint: Endpoint,
+    pub connection: Connection,
+    pub send: SendStream,
+    pub recv: RecvStream,
+}
+
+impl RawClient {
+    /// Writes a length prefix and payload, without checking either
+    pub async fn write_frame(&mut self, len: u32, payload: &[u8]) -> TestResult {
+        self.send.write_all(&len.to_be_bytes()).await?;
+        self.send.write_all(payload).await?;
+        self.send.flush().await?;
+        Ok(())
+    }
+
+    /// Encodes the command with msgpack and writes it as a frame
+    pub async fn send(&mut self, command: CommandKind) -> TestResult {
+        let payload = Codec::MessagePack.encode(&command)?;
+        self.write_frame(u32::try_from(payload.len())?, &payload)
+            .await
+    }
+
+    /// Reads a frame and decodes it as an event
+    pub async fn recv(&mut self) -> TestResult<EventKind> {
+        Ok(Codec::MessagePack.decode(&self.read_frame().await?)?)
+    }
+
+    /// Reads a frame, assuming it isn't compressed
+    pub async fn read_frame(&mut self) -> TestResult<Vec<u8>> {
+        let len = timeout(self.recv.read_u32()).await??;
+        if len > MAX_MESSAGE_SIZE {
+            return Err(format!("frame of {len} bytes is too large").into());
+        }
+        let mut payload = vec![0; len as usize];
+        timeout(self.recv.read_exact(&mut payload)).await??;
+        Ok(payload)
+    }
+}
@@ -0,0 +1,224 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # End to end
+//! Tests the network against real clients over QUIC on localhost.
+
+mod harness;
+#[cfg(feature = "websocket")]
+mod websocket;
+
+use harness::{TestResult, TestServer, VERSION, eventually, timeout};
+use network::{
+    capture::{self, Message},
+    frame::MAX_MESSAGE_SIZE,
+};
+use protocol::{
+    PROTOCOL_VERSION,
+    command::{CommandKind, join::Join},
+    event::EventKind,
+};
+use quinn::ConnectionError;
+
+const fn join(uuid: u64) -> Join {
+    Join {
+        uuid,
+        hash: 0,
+        ip: None,
+        connection: None,
+        compression: None,
+        dictionary: None,
+    }
+}
+
+fn closed_with(error: &ConnectionError, code: u32) -> bool {
+    matches!(error, ConnectionError::ApplicationClosed(close) if close.error_code == code.into())
+}
+
+#[test]
+fn exits_when_the_socket_is_taken() -> TestResult {
+    let taken = std::net::UdpSocket::bind((std::net::Ipv4Addr::LOCALHOST, 0))?;
+    let socket = taken.local_addr()?;
+
+    let exit = TestServer::exit_with(|config| config.network.socket = socket)?;
+    assert!(exit.is_some_and(|exit| exit.is_error()));
+    Ok(())
+}
+
+#[tokio::test]
+async fn join_flow() -> TestResult {
+    let server = TestServer::start()?;
+
+    let mut first = server.connect().await?;
+    let accept = timeout(first.join(1, 0)).await??;
+    assert_eq!(accept.uuid, 1);
+    assert!(matches!(
+        timeout(first.recv()).await??,
+        Some(EventKind::PlayerJoined(_))
+    ));
+
+    let mut second = server.connect().await?;
+    let accept = timeout(second.join(2, 0)).await??;
+    assert_eq!(accept.uuid, 2);
+
+    // the first player is told about the second one
+    assert!(matches!(
+        timeout(first.recv()).await??,
+        Some(EventKind::PlayerJoined(_))
+    ));
+    Ok(())
+}
+
+#[tokio::test]
+async fn oversized_frame_closes_connection() -> TestResult {
+    let server = TestServer::start()?;
+    let mut client = server.connect_raw().await?;
+
+    client.write_frame(MAX_MESSAGE_SIZE + 1, &[]).await?;
+
+    let error = timeout(client.connection.closed()).await?;
+    assert!(closed_with(&error, 0), "{error}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn malformed_msgpack_is_skipped() -> TestResult {
+    let server = TestServer::start()?;
+    let mut client = server.connect_raw().await?;
+
+    // 0xc1 is never used by msgpack
+    client.write_frame(3, &[0xc1, 0xc1, 0xc1]).await?;
+
+    // the connection survives and still accepts valid commands
+    client.send(CommandKind::Join(join(7))).await?;
+    let event = client.recv().await?;
+    assert!(matches!(event, EventKind::JoinAccept(accept) if accept.uuid == 7));
+    Ok(())
+}
+
+#[tokio::test]
+async fn migrated_connection_keeps_its_id() -> TestResult {
+    let server = TestServer::start()?;
+    let mut client = server.connect_raw().await?;
+
+    client.send(CommandKind::Join(join(3))).await?;
+    let EventKind::JoinAccept(before) = client.recv().await? else {
+        return Err("expected a JoinAccept".into());
+    };
+
+    // moving to a new socket changes the address the server sees
+    let old = client.endpoint.local_addr()?;
+    client.endpoint.rebind(std::net::UdpSocket::bind((
+        std::net::Ipv4Addr::LOCALHOST,
+        0,
+    ))?)?;
+    assert_ne!(client.endpoint.local_addr()?, old);
+
+    // a JoinAccept only reaches the connection the Join came in on
+    client.send(CommandKind::Join(join(3))).await?;
+    loop {
+        match client.recv().await? {
+            EventKind::JoinAccept(after) => {
+                assert_eq!(after.connection, before.connection);
+                break;
+            }
+            EventKind::PlayerJoined(_) => {}
+            event => return Err(format!("unexpected {event:?}").into()),
+        }
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn status_counts_joined_players() -> TestResult {
+    let server = TestServer::start_with(|config| {
+        config.motd = "welcome".to_owned();
+        config.max_players = 8;
+    })?;
+
+    let status = server.status().await?;
+    assert_eq!(status.motd, "welcome");
+    assert_eq!(status.max_players, 8);
+    assert_eq!(status.version, VERSION);
+    assert_eq!(status.protocol, PROTOCOL_VERSION);
+
+    // a connection only counts once its player joined
+    let mut client = server.connect().await?;
+    assert_eq!(server.online().await?, 0);
+    timeout(client.join(1, 0)).await??;
+    eventually(|| async { Ok(server.online().await? == 1) }).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn disconnect_removes_client() -> TestResult {
+    let server = TestServer::start()?;
+    assert_eq!(server.online().await?, 0);
+
+    let mut client = server.connect().await?;
+    timeout(client.join(1, 0)).await??;
+    eventually(|| async { Ok(server.online().await? == 1) }).await?;
+
+    client.close();
+    eventually(|| async { Ok(server.online().await? == 0) }).await?;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn shutdown_closes_connections() -> TestResult {
+    let mut server = TestServer::start()?;
+    let mut client = server.connect().await?;
+    timeout(client.join(1, 0)).await??;
+    let (commands, _events) = client.split();
+
+    tokio::task::block_in_place(|| server.shutdown());
+
+    let error = timeout(commands.connection().closed()).await?;
+    assert!(closed_with(&error, 0x100), "{error}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn network_shutdown_closes_connections() -> TestResult {
+    let server = TestServer::start()?;
+    let mut client = server.connect().await?;
+    timeout(client.join(1, 0)).await??;
+    let (commands, _events) = client.split();
+
+    server.stop_network();
+
+    let error = timeout(commands.connection().closed()).await?;
+    assert!(closed_with(&error, 0x100), "{error}");
+    assert!(server.connect().await.is_err());
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn recorded_session_replays() -> TestResult {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("session.cap");
+
+    let mut server = TestServer::start_with(|config| config.network.record = Some(path.clone()))?;
+    let mut client = server.connect().await?;
+    timeout(client.join(5, 0)).await??;
+    client.close();
+    tokio::task::block_in_place(|| server.shutdown());
+
+    let records = capture::read_file(&path)?;
+    let session = records
+        .iter()
+        .find(|record| matches!(record.message, Message::Inbound(_)))
+        .map(|record| record.session)
+        .ok_or("no command was recorded")?;
+    assert!(records.iter().any(|record| record.session == session
+        && matches!(&record.message, Message::Outbound(event) if matches!(&event.message, EventKind::JoinAccept(accept) if accept.uuid == 5))));
+
+    let server = TestServer::start()?;
+    let mut client = server.connect().await?;
+    assert_eq!(client.replay(&records, session).await?, 1);
+    assert!(matches!(
+        timeout(client.recv()).await??,
+        Some(EventKind::JoinAccept(accept)) if accept.uuid == 5
+    ));
+    Ok(())
+}
@@ -0,0 +1,97 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # WebSocket
+//! Tests the WebSocket gateway, only built with the `websocket` feature.
+
+use crate::{
+    harness::{TestResult, TestServer, eventually, timeout},
+    join,
+};
+use futures_util::{SinkExt, StreamExt};
+use network::Codec;
+use protocol::{command::CommandKind, event::EventKind};
+use rustls_pki_types::ServerName;
+use std::{net::Ipv4Addr, sync::Arc};
+use tokio::net::TcpStream;
+use tokio_rustls::{TlsConnector, client::TlsStream};
+use tokio_tungstenite::{
+    WebSocketStream, client_async,
+    tungstenite::{
+        Message,
+        client::IntoClientRequest,
+        http::{HeaderValue, header::SEC_WEBSOCKET_PROTOCOL},
+    },
+};
+
+type WebSocket = WebSocketStream<TlsStream<TcpStream>>;
+
+fn start() -> TestResult<TestServer> {
+    TestServer::start_with(|config| {
+        config.network.websocket = Some((Ipv4Addr::LOCALHOST, 0).into());
+    })
+}
+
+/// Connects over TLS and asks for the given codec
+async fn connect(server: &TestServer, codec: Codec) -> TestResult<WebSocket> {
+    let addr = server.websocket.ok_or("the gateway isn't bound")?;
+    let connector = TlsConnector::from(Arc::new(server.tls_config(vec![b"http/1.1".to_vec()])?));
+    let stream = timeout(TcpStream::connect(addr)).await??;
+    let stream = timeout(connector.connect(ServerName::try_from("localhost")?, stream)).await??;
+
+    let mut request = format!("wss://localhost:{}/", addr.port()).into_client_request()?;
+    request.headers_mut().insert(
+        SEC_WEBSOCKET_PROTOCOL,
+        HeaderValue::from_static(codec.websocket_protocol()),
+    );
+    let (websocket, response) = timeout(Box::pin(client_async(request, stream))).await??;
+    assert_eq!(
+        response.headers().get(SEC_WEBSOCKET_PROTOCOL),
+        Some(&HeaderValue::from_static(codec.websocket_protocol()))
+    );
+    Ok(websocket)
+}
+
+/// Sends a command as a single frame in a binary message
+async fn send(websocket: &mut WebSocket, codec: Codec, command: CommandKind) -> TestResult {
+    let payload = codec.encode(&command)?;
+    let mut frame = u32::try_from(payload.len())?.to_be_bytes().to_vec();
+    frame.extend_from_slice(&payload);
+    websocket.send(Message::Binary(frame.into())).await?;
+    Ok(())
+}
+
+/// Receives the next binary message, assuming it holds a single frame
+async fn recv(websocket: &mut WebSocket, codec: Codec) -> TestResult<EventKind> {
+    loop {
+        let message = timeout(websocket.next())
+            .await?
+            .ok_or("the connection was closed")??;
+        if let Message::Binary(data) = message {
+            let payload = data.get(4..).ok_or("the frame has no length prefix")?;
+            return Ok(codec.decode(payload)?);
+        }
+    }
+}
+
+#[tokio::test]
+async fn websocket_join_flow() -> TestResult {
+    let server = start()?;
+    let mut websocket = Box::pin(connect(&server, Codec::Json)).await?;
+
+    send(&mut websocket, Codec::Json, CommandKind::Join(join(9))).await?;
+    let event = recv(&mut websocket, Codec::Json).await?;
+    assert!(matches!(event, EventKind::JoinAccept(accept) if accept.uuid == 9));
+    Ok(())
+}
+
+#[tokio::test]
+async fn network_shutdown_stops_the_gateway() -> TestResult {
+    let server = start()?;
+    let addr = server.websocket.ok_or("the gateway isn't bound")?;
+    drop(Box::pin(connect(&server, Codec::MessagePack)).await?);
+
+    server.stop_network();
+
+    eventually(|| async { Ok(TcpStream::connect(addr).await.is_err()) }).await
+}
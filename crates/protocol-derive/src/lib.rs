@@ -0,0 +1,182 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # Protocol Derive
+//! Derive macros for the `protocol` crate.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{
+    Data, DeriveInput, Fields, Ident, Token, Type, parse::ParseStream, parse_macro_input,
+    spanned::Spanned,
+};
+
+/// Wires up every variant of a message enum, like `CommandKind` or `EventKind`.
+///
+/// Every variant has to hold a single Bevy event. The derive generates:
+/// - `visit_variants`, which calls a `protocol::MessageVisitor` with the type
+///   of every variant, used to register the Bevy events and the network bridge
+/// - `write_event`, which writes the held value as a Bevy event into a `World`
+///   and returns its type and event id
+/// - `NAMES` and `name`, the names of the variants, used to label metrics
+///
+/// Adding a variant to the enum is all it takes to add a message.
+#[proc_macro_derive(Messages)]
+pub fn derive_messages(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let Data::Enum(data) = &input.data else {
+        return Err(syn::Error::new(
+            input.span(),
+            "`Messages` can only be derived for enums",
+        ));
+    };
+
+    let variants = data
+        .variants
+        .iter()
+        .map(|variant| match &variant.fields {
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                Ok((&variant.ident, &fields.unnamed[0].ty))
+            }
+            _ => Err(syn::Error::new(
+                variant.span(),
+                "every variant has to hold exactly one message",
+            )),
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let name = &input.ident;
+    let idents: Vec<_> = variants.iter().map(|(ident, _)| *ident).collect();
+    let names: Vec<_> = idents.iter().map(ToString::to_string).collect();
+    let types: Vec<&Type> = variants.iter().map(|(_, ty)| *ty).collect();
+
+    Ok(quote! {
+        impl #name {
+            /// Names of all variants, in declaration order
+            pub const NAMES: &'static [&'static str] = &[#( #names ),*];
+
+            /// Name of the variant
+            #[must_use]
+            pub const fn name(&self) -> &'static str {
+                match self {
+                    #( Self::#idents(_) => #names, )*
+                }
+            }
+
+            /// Calls the visitor with the type held by every variant
+            pub fn visit_variants<V: ::protocol::MessageVisitor<Self>>(visitor: &mut V) {
+                #( visitor.visit::<#types>(); )*
+            }
+
+            /// Writes the held message as a Bevy event into the world.
+            ///
+            /// Returns the type of the message and the id of the written
+            /// event, which tell events of different types apart.
+            pub fn write_event(
+                self,
+                world: &mut ::bevy::ecs::world::World,
+            ) -> ::core::option::Option<(::core::any::TypeId, usize)> {
+                match self {
+                    #(
+                        Self::#idents(message) => world
+                            .send_event(message)
+                            .map(|id| (::core::any::TypeId::of::<#types>(), id.id)),
+                    )*
+                }
+            }
+        }
+    })
+}
+
+/// Implements `protocol::Targetable` for an event from its `#[target(...)]`
+/// attribute, and describes the recipients in the `TARGET` constant used for
+/// the `x-target` of the schema.
+///
+/// The attribute names a variant of `protocol::Target`, and for every
+/// variant but `Everyone` the field holding the id or the ids:
+/// `#[target(Everyone)]`, `#[target(Player = uuid)]`,
+/// `#[target(Group = uuids)]`, `#[target(EveryoneExcept = uuid)]` or
+/// `#[target(EveryoneExceptGroup = uuids)]`.
+#[proc_macro_derive(Targetable, attributes(target))]
+pub fn derive_targetable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_targetable(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand_targetable(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let attr = input
+        .attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("target"))
+        .ok_or_else(|| syn::Error::new(input.span(), "missing `#[target(...)]` attribute"))?;
+    let (variant, field) = attr.parse_args_with(|input: ParseStream| {
+        let variant: Ident = input.parse()?;
+        let field = if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            Some(input.parse::<Ident>()?)
+        } else {
+            None
+        };
+        Ok((variant, field))
+    })?;
+
+    let (target, description) = match (variant.to_string().as_str(), &field) {
+        ("Everyone", None) => (quote!(::protocol::Target::Everyone), "Everyone".to_owned()),
+        ("Player", Some(field)) => (
+            quote!(::protocol::Target::Player(self.#field)),
+            format!("The player in `{field}`"),
+        ),
+        ("Group", Some(field)) => (
+            quote!(::protocol::Target::Group(self.#field.clone())),
+            format!("The players in `{field}`"),
+        ),
+        ("EveryoneExcept", Some(field)) => (
+            quote!(::protocol::Target::EveryoneExcept(self.#field)),
+            format!("Everyone except the player in `{field}`"),
+        ),
+        ("EveryoneExceptGroup", Some(field)) => (
+            quote!(::protocol::Target::EveryoneExceptGroup(self.#field.clone())),
+            format!("Everyone except the players in `{field}`"),
+        ),
+        ("Everyone", Some(_)) => {
+            return Err(syn::Error::new(
+                variant.span(),
+                "`Everyone` doesn't take a field",
+            ));
+        }
+        ("Player" | "Group" | "EveryoneExcept" | "EveryoneExceptGroup", None) => {
+            return Err(syn::Error::new(
+                variant.span(),
+                format!("`{variant}` needs the field holding the ids, like `{variant} = uuid`"),
+            ));
+        }
+        _ => {
+            return Err(syn::Error::new(
+                variant.span(),
+                format!("`{variant}` isn't a variant of `Target`"),
+            ));
+        }
+    };
+
+    let name = &input.ident;
+    Ok(quote! {
+        impl #name {
+            /// Who receives this event, the `x-target` of the schema
+            pub const TARGET: &'static str = #description;
+        }
+
+        impl ::protocol::Targetable for #name {
+            fn get_target(&self) -> ::protocol::Target {
+                #target
+            }
+        }
+    })
+}
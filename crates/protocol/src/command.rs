@@ -9,7 +9,18 @@ pub mod join;
 
 /// Command from the client to the server
 #[derive(
-    serde::Deserialize, serde::Serialize, Debug, Ord, PartialOrd, Eq, PartialEq, Copy, Clone, Hash,
+    serde::Deserialize,
+    serde::Serialize,
+    Debug,
+    Ord,
+    PartialOrd,
+    Eq,
+    PartialEq,
+    Copy,
+    Clone,
+    Hash,
+    schemars::JsonSchema,
+    protocol_derive::Messages,
 )]
 #[enum_dispatch::enum_dispatch]
 #[non_exhaustive]
@@ -4,8 +4,7 @@
 //! # Join
 //! For information about the protocol please go to the following [url](https://Sietse2202.github.io/crypts-of-the-lost/).
 
-#![expect(missing_docs)]
-
+use crate::Compression;
 use bevy::ecs::event::Event;
 use std::net::SocketAddr;
 
@@ -22,9 +21,36 @@ use std::net::SocketAddr;
     Clone,
     Hash,
     Event,
+    schemars::JsonSchema,
 )]
+#[schemars(extend("x-since" = 1), example = Self::example())]
 pub struct Join {
+    /// Id of the player that joins
     pub uuid: u64,
+    /// Opaque value, the server neither checks nor uses it yet
     pub hash: u64,
-    pub ip: Option<SocketAddr>, // needed for the network handler
+    /// Filled in by the server, the address the client connects from
+    pub ip: Option<SocketAddr>,
+    /// Filled in by the server, the id of the connection
+    #[serde(default)]
+    pub connection: Option<u64>,
+    /// Preferred compression of the client
+    #[serde(default)]
+    pub compression: Option<Compression>,
+    /// Id of the compression dictionary the client has
+    #[serde(default)]
+    pub dictionary: Option<u32>,
+}
+
+impl Join {
+    const fn example() -> Self {
+        Self {
+            uuid: 42,
+            hash: 1337,
+            ip: None,
+            connection: None,
+            compression: Some(Compression::Zstd),
+            dictionary: None,
+        }
+    }
 }
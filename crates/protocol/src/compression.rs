@@ -0,0 +1,31 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # Compression
+//! Defines the compression algorithms that can be negotiated when joining.
+
+/// Compression algorithm used for frames sent from the server to the client.
+///
+/// The client requests one in [`Join`](crate::command::join::Join) and the
+/// server confirms it in [`JoinAccept`](crate::event::JoinAccept). Only frames
+/// sent after the `JoinAccept` may be compressed.
+#[derive(
+    serde::Deserialize,
+    serde::Serialize,
+    Debug,
+    Ord,
+    PartialOrd,
+    Eq,
+    PartialEq,
+    Copy,
+    Clone,
+    Hash,
+    schemars::JsonSchema,
+)]
+#[non_exhaustive]
+pub enum Compression {
+    /// [Zstandard](https://facebook.github.io/zstd/), best ratio
+    Zstd,
+    /// [LZ4](https://lz4.org/) block format with the uncompressed size prepended, fastest
+    Lz4,
+}
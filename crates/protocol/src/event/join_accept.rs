@@ -4,22 +4,48 @@
 //! # `JoinAccept`
 //! For information about the protocol please go to the following [url](https://Sietse2202.github.io/crypts-of-the-lost/).
 
-#![expect(missing_docs)]
-
+use crate::Compression;
 use bevy::ecs::event::Event;
-use std::net::SocketAddr;
 
 /// Event from the server to the client whose join command got accepted
-#[derive(serde::Deserialize, serde::Serialize, Debug, Eq, PartialEq, Clone, Event)]
+#[derive(
+    serde::Deserialize,
+    serde::Serialize,
+    Debug,
+    Eq,
+    PartialEq,
+    Clone,
+    Event,
+    schemars::JsonSchema,
+    protocol_derive::Targetable,
+)]
+#[target(Player = uuid)]
+#[schemars(
+    extend("x-since" = 1, "x-target" = Self::TARGET),
+    example = Self::example()
+)]
 pub struct JoinAccept {
-    pub ip: SocketAddr,
+    /// Copied from `Join::connection`, needed for the network handler
+    pub connection: u64,
+    /// Id of the player that joined
     pub uuid: u64,
+    /// Compression used for the following frames, copied from `Join::compression`
+    #[serde(default)]
+    pub compression: Option<Compression>,
+    /// Dictionary used for the compression, copied from `Join::dictionary`
+    #[serde(default)]
+    pub dictionary: Option<u32>,
 }
 
-impl crate::event::Event for JoinAccept {}
-
-impl crate::target::Targetable for JoinAccept {
-    fn get_target(&self) -> crate::target::Target {
-        crate::target::Target::Player(self.uuid)
+impl JoinAccept {
+    const fn example() -> Self {
+        Self {
+            connection: 7,
+            uuid: 42,
+            compression: Some(Compression::Zstd),
+            dictionary: None,
+        }
     }
 }
+
+impl crate::event::Event for JoinAccept {}
@@ -6,14 +6,20 @@
 
 use bevy::ecs::event::Event;
 
-/// New player joined. Gets sent to everyone except the new player
-#[derive(serde::Deserialize, serde::Serialize, Debug, Eq, PartialEq, Clone, Event)]
+/// New player joined
+#[derive(
+    serde::Deserialize,
+    serde::Serialize,
+    Debug,
+    Eq,
+    PartialEq,
+    Clone,
+    Event,
+    schemars::JsonSchema,
+    protocol_derive::Targetable,
+)]
+#[target(Everyone)]
+#[schemars(extend("x-since" = 1, "x-target" = Self::TARGET), example = Self {})]
 pub struct PlayerJoined {}
 
 impl crate::Event for PlayerJoined {}
-
-impl crate::Targetable for PlayerJoined {
-    fn get_target(&self) -> crate::Target {
-        crate::Target::Everyone
-    }
-}
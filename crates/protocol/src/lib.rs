@@ -6,29 +6,129 @@
 
 #![expect(clippy::multiple_crate_versions)]
 
-use bevy::app::Plugin;
+// lets the code generated by `protocol_derive` refer to `::protocol` inside this crate
+extern crate self as protocol;
+
+use bevy::app::{App, Plugin};
 
 pub mod command;
+pub mod compression;
 pub mod event;
+mod message;
+pub mod schema;
+pub mod status;
 mod target;
+pub mod tick;
 
 pub use command::Command;
+pub use compression::Compression;
 pub use event::Event;
+pub use message::MessageVisitor;
+pub use status::Status;
 pub use target::{Target, Targetable};
+pub use tick::{ServerTick, Stamped};
+
+/// Version of the protocol, increased on every incompatible change
+pub const PROTOCOL_VERSION: u32 = 1;
 
 /// bevy plugin for the protocol
 ///
-/// adds all events and commands as bevy events
+/// adds all events and commands as bevy events, and the [`ServerTick`]
 #[derive(Debug)]
 pub struct Protocol;
 
 impl Plugin for Protocol {
-    fn build(&self, app: &mut bevy::app::App) {
-        // Command events
-        app.add_event::<command::join::Join>();
+    fn build(&self, app: &mut App) {
+        let mut registrar = EventRegistrar { app };
+        command::CommandKind::visit_variants(&mut registrar);
+        event::EventKind::visit_variants(&mut registrar);
+        app.init_resource::<ServerTick>();
+    }
+}
+
+/// Adds the type of every command and event as a bevy event
+struct EventRegistrar<'a> {
+    app: &'a mut App,
+}
+
+impl<K> MessageVisitor<K> for EventRegistrar<'_> {
+    fn visit<T>(&mut self)
+    where
+        T: bevy::ecs::event::Event + Clone + Into<K>,
+    {
+        self.app.add_event::<T>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::event::Events;
+    use command::{CommandKind, join::Join};
+    use event::{EventKind, JoinAccept, PlayerJoined};
+    use std::any::TypeId;
+
+    /// Collects the visited types
+    #[derive(Default)]
+    struct Types(Vec<TypeId>);
+
+    impl<K> MessageVisitor<K> for Types {
+        fn visit<T>(&mut self)
+        where
+            T: bevy::ecs::event::Event + Clone + Into<K>,
+        {
+            self.0.push(TypeId::of::<T>());
+        }
+    }
+
+    #[test]
+    fn variants_are_named_and_visited_in_order() {
+        assert_eq!(EventKind::NAMES, ["JoinAccept", "PlayerJoined"]);
+        assert_eq!(CommandKind::NAMES, ["Join"]);
+        assert_eq!(
+            EventKind::PlayerJoined(PlayerJoined {}).name(),
+            "PlayerJoined"
+        );
+
+        let mut types = Types::default();
+        EventKind::visit_variants(&mut types);
+        assert_eq!(
+            types.0,
+            [TypeId::of::<JoinAccept>(), TypeId::of::<PlayerJoined>()]
+        );
+    }
+
+    #[test]
+    fn commands_become_bevy_events() {
+        let mut app = App::new();
+        app.add_plugins(Protocol);
+
+        let join = Join {
+            uuid: 42,
+            hash: 0,
+            ip: None,
+            connection: None,
+            compression: None,
+            dictionary: None,
+        };
+        // ids count per type, the type tells them apart
+        assert_eq!(
+            CommandKind::Join(join).write_event(app.world_mut()),
+            Some((TypeId::of::<Join>(), 0))
+        );
+        assert_eq!(
+            EventKind::PlayerJoined(PlayerJoined {}).write_event(app.world_mut()),
+            Some((TypeId::of::<PlayerJoined>(), 0))
+        );
 
-        // Event events
-        app.add_event::<event::JoinAccept>()
-            .add_event::<event::PlayerJoined>();
+        let events = app.world().resource::<Events<Join>>();
+        assert_eq!(
+            events.iter_current_update_events().collect::<Vec<_>>(),
+            [&join]
+        );
+        assert_eq!(
+            CommandKind::Join(join).write_event(app.world_mut()),
+            Some((TypeId::of::<Join>(), 1))
+        );
     }
 }
@@ -0,0 +1,17 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # Message
+//! Lets other crates act on the type of every command or event, without
+//! listing them by hand.
+
+/// Visits the type held by every variant of a message enum, see
+/// `CommandKind::visit_variants` and `EventKind::visit_variants`.
+///
+/// `K` is the message enum, so the visitor can convert to it.
+pub trait MessageVisitor<K> {
+    /// Called once for the type held by every variant
+    fn visit<T>(&mut self)
+    where
+        T: bevy::ecs::event::Event + Clone + Into<K>;
+}
@@ -0,0 +1,82 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # Schema
+//! Exports the protocol as a versioned JSON Schema, so bindings in other
+//! languages can be generated instead of reverse-engineered.
+//!
+//! The schema describes the serde data model of the messages, which every
+//! codec encodes: enums are externally tagged, so a command is an object with
+//! a single key naming the variant. The exported schema is kept in
+//! `docs/protocol/schema.json` and regenerated with `cargo xtask schema`.
+
+use crate::{PROTOCOL_VERSION, Status, Target, command::CommandKind, event::EventKind};
+use schemars::generate::SchemaSettings;
+use serde_json::{Value, json};
+
+/// Creates the JSON Schema of the entire protocol.
+///
+/// The root lists the commands, events, [`Target`] and [`Status`], all types
+/// they use are under `$defs`. `version` is the [`PROTOCOL_VERSION`].
+#[must_use]
+pub fn protocol_schema() -> Value {
+    let mut generator = SchemaSettings::draft2020_12().into_generator();
+    let command = generator.subschema_for::<CommandKind>();
+    let event = generator.subschema_for::<EventKind>();
+    let target = generator.subschema_for::<Target>();
+    let status = generator.subschema_for::<Status>();
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "Crypts of the Lost protocol",
+        "version": PROTOCOL_VERSION,
+        "type": "object",
+        "properties": {
+            "command": command,
+            "event": event,
+            "target": target,
+            "status": status,
+        },
+        "$defs": generator.take_definitions(true),
+    })
+}
+
+/// [`protocol_schema`] as pretty printed JSON, ending with a newline
+///
+/// # Errors
+/// Returns a `serde_json::Error` when the schema can't be serialized.
+pub fn protocol_schema_string() -> Result<String, serde_json::Error> {
+    let mut schema = serde_json::to_string_pretty(&protocol_schema())?;
+    schema.push('\n');
+    Ok(schema)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn committed_schema_is_up_to_date() -> Result<(), serde_json::Error> {
+        let committed = include_str!("../../../docs/protocol/schema.json");
+        assert!(
+            committed == protocol_schema_string()?,
+            "docs/protocol/schema.json is outdated, run `cargo xtask schema`"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn every_message_is_described() {
+        let schema = protocol_schema();
+        for name in [
+            "CommandKind",
+            "EventKind",
+            "Join",
+            "JoinAccept",
+            "PlayerJoined",
+        ] {
+            assert!(schema["$defs"].get(name).is_some(), "{name} is missing");
+        }
+        assert_eq!(schema["version"], PROTOCOL_VERSION);
+    }
+}
@@ -0,0 +1,27 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # Status
+//! Defines the status a server reports without having to join it.
+
+/// Information about a server, sent to anyone who connects with the status
+/// ALPN protocol instead of joining.
+///
+/// Meant for server lists and monitoring, so it is cheap to ask for.
+#[derive(
+    serde::Deserialize, serde::Serialize, Debug, Eq, PartialEq, Clone, Hash, schemars::JsonSchema,
+)]
+pub struct Status {
+    /// Message of the day
+    pub motd: String,
+    /// Amount of clients currently connected
+    pub online: u32,
+    /// Maximum amount of players on the server
+    pub max_players: u32,
+    /// Version of the server software
+    pub version: String,
+    /// Version of the protocol the server speaks, see [`PROTOCOL_VERSION`](crate::PROTOCOL_VERSION)
+    pub protocol: u32,
+    /// Seconds since the server started
+    pub uptime: u64,
+}
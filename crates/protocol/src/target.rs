@@ -9,7 +9,9 @@ use crate::event::EventKind;
 use std::collections::HashSet;
 
 /// The target(s) to send the event to.
-#[derive(serde::Deserialize, serde::Serialize, Debug, Eq, PartialEq, Clone)]
+#[derive(
+    serde::Deserialize, serde::Serialize, Debug, Eq, PartialEq, Clone, schemars::JsonSchema,
+)]
 pub enum Target {
     /// Sends the event to everyone
     Everyone,
@@ -41,7 +43,8 @@ impl Target {
     }
 }
 
-/// no doc yet
+/// Tells who receives an event, derived from the `#[target(...)]`
+/// attribute of the event with `protocol_derive::Targetable`
 #[enum_dispatch::enum_dispatch(EventKind)]
 pub trait Targetable {
     /// Returns the [`Target`] associated with this type
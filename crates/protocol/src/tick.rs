@@ -0,0 +1,54 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # Tick
+//! The simulation of the server advances in fixed ticks, this module
+//! defines the counter of those ticks.
+
+use bevy::ecs::resource::Resource;
+use serde::{Deserialize, Serialize};
+
+/// The tick the server simulation is on, increased by one at the start of
+/// every fixed step and never decreased.
+///
+/// Added as a resource by the [`Protocol`](crate::Protocol) plugin.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Resource,
+    Serialize,
+    Deserialize,
+)]
+pub struct ServerTick(pub u64);
+
+impl ServerTick {
+    /// Moves to the next tick
+    pub const fn advance(&mut self) {
+        self.0 += 1;
+    }
+
+    /// Stamps a message with this tick
+    #[must_use]
+    pub const fn stamp<T>(self, message: T) -> Stamped<T> {
+        Stamped {
+            tick: self,
+            message,
+        }
+    }
+}
+
+/// A message together with the tick it was produced on
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stamped<T> {
+    /// The tick the message was produced on
+    pub tick: ServerTick,
+    /// The message
+    pub message: T,
+}
@@ -0,0 +1,166 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # Cli
+//! The command line of the server. Next to its own options, it accepts an
+//! argument for every top-level config section, such as `--max_players 5` or
+//! `--network '{"socket": "0.0.0.0:4000"}'`, which override the config files.
+
+use clap::{Arg, ArgAction, ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand};
+use config::{Config, ConfigOptions};
+use konfik::config_meta::ConfigMetadata;
+use std::{collections::BTreeMap, ffi::OsString, path::PathBuf};
+
+/// Crypts of the Lost server, runs the server when no command is given
+#[derive(Debug, Parser)]
+#[command(version)]
+pub struct Cli {
+    /// The base config file, `config.toml` in the working directory by default
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+    /// Profile merged on top of the config file, `dev` reads `config.dev.toml`
+    #[arg(long, global = true, env = "COTL_PROFILE")]
+    pub profile: Option<String>,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// Top-level config fields set by their argument, with the raw value
+    #[arg(skip)]
+    pub overrides: BTreeMap<String, String>,
+}
+
+/// The commands of the server
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Runs the server, the default when no command is given
+    Run,
+    /// Loads and validates the config without starting the server
+    CheckConfig,
+    /// Generates a self-signed certificate and its private key
+    GenCert {
+        /// Hostnames and IP addresses the certificate is valid for
+        #[arg(default_value = "localhost")]
+        hostnames: Vec<String>,
+        /// Where to write the certificate, `network.certs` of the config by default
+        #[arg(long)]
+        certs: Option<PathBuf>,
+        /// Where to write the private key, `network.key` of the config by default
+        #[arg(long)]
+        key: Option<PathBuf>,
+        /// Overwrites existing files
+        #[arg(long)]
+        force: bool,
+    },
+    /// Prints the SHA-256 fingerprint of the certificate, for clients to pin
+    PrintFingerprint {
+        /// The certificate, `network.certs` of the config by default
+        #[arg(long)]
+        certs: Option<PathBuf>,
+    },
+    /// Migrates the persistent store to the current version
+    Migrate,
+    /// Creates and describes config files
+    #[command(subcommand)]
+    Config(ConfigCommand),
+}
+
+/// Commands for the config file
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// Writes a config with every default value, commented with its description
+    Init {
+        /// Where to write the config, the `--config` path or `config.toml` by default
+        path: Option<PathBuf>,
+        /// Overwrites an existing file
+        #[arg(long)]
+        force: bool,
+    },
+    /// Prints the JSON Schema of the config, for editors to validate and complete config files
+    Schema {
+        /// Writes the schema to a file instead
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+}
+
+impl Cli {
+    /// Parses the arguments, including the config overrides
+    pub fn parse_args() -> Result<Self, clap::Error> {
+        Self::parse_from_args(std::env::args_os())
+    }
+
+    fn parse_from_args<I, T>(args: I) -> Result<Self, clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let fields = Config::config_metadata().fields;
+        let overrides = fields.iter().map(|field| {
+            Arg::new(field.name.clone())
+                .long(field.name.clone())
+                .help(format!(
+                    "Overrides `{}`, tables are given as JSON",
+                    field.name
+                ))
+                .global(true)
+                .value_name("VALUE")
+                .action(ArgAction::Set)
+                .help_heading("Config overrides")
+        });
+        let matches = Self::command().args(overrides).try_get_matches_from(args)?;
+        let mut cli = Self::from_arg_matches(&matches)?;
+        cli.overrides = fields
+            .iter()
+            .filter_map(|field| {
+                let value = find_override(&matches, &field.name)?;
+                Some((field.name.clone(), value))
+            })
+            .collect();
+        Ok(cli)
+    }
+
+    /// The files to read the config from, and the overrides
+    pub fn config_options(&self) -> ConfigOptions {
+        ConfigOptions {
+            path: self.config.clone(),
+            profile: self.profile.clone(),
+            overrides: self.overrides.clone(),
+        }
+    }
+}
+
+/// The value of a global override, wherever in the subcommands it was given
+fn find_override(matches: &ArgMatches, name: &str) -> Option<String> {
+    if let Some(value) = matches.get_one::<String>(name) {
+        return Some(value.clone());
+    }
+    let (_, matches) = matches.subcommand()?;
+    find_override(matches, name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overrides_are_read_in_either_form() -> Result<(), clap::Error> {
+        let cli = Cli::parse_from_args([
+            "server",
+            "--max_players=5",
+            "check-config",
+            "--network",
+            r#"{"socket": "0.0.0.0:4000"}"#,
+        ])?;
+        assert!(matches!(cli.command, Some(Command::CheckConfig)));
+        assert_eq!(
+            cli.overrides,
+            BTreeMap::from([
+                ("max_players".to_owned(), "5".to_owned()),
+                (
+                    "network".to_owned(),
+                    r#"{"socket": "0.0.0.0:4000"}"#.to_owned()
+                ),
+            ])
+        );
+        Ok(())
+    }
+}
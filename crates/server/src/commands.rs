@@ -0,0 +1,211 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # Commands
+//! The maintenance commands of the server, everything but running it.
+
+use crate::cli::ConfigCommand;
+use config::{
+    ConfigError, ConfigOptions, load_config, parse_config,
+    schema::{config_schema_string, default_config},
+};
+use network::Certs;
+use std::{
+    error::Error,
+    fs::OpenOptions,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// Result of a command, errors are printed before exiting
+pub type CommandResult = Result<(), Box<dyn Error>>;
+
+/// Loads and validates the config, printing every problem
+pub fn check_config(options: &ConfigOptions) -> CommandResult {
+    parse_config(options)?;
+    eprintln!("the config is valid");
+    Ok(())
+}
+
+/// Writes a self-signed certificate for the hostnames, and its key
+pub fn gen_cert(
+    options: &ConfigOptions,
+    hostnames: Vec<String>,
+    certs: Option<PathBuf>,
+    key: Option<PathBuf>,
+    force: bool,
+) -> CommandResult {
+    let (certs, key) = match (certs, key) {
+        (Some(certs), Some(key)) => (certs, key),
+        (certs, key) => {
+            let (default_certs, default_key) = configured_certs(options)?;
+            (certs.unwrap_or(default_certs), key.unwrap_or(default_key))
+        }
+    };
+    for path in [&certs, &key] {
+        refuse_overwrite(path, force)?;
+    }
+
+    let generated = rcgen::generate_simple_self_signed(hostnames)?;
+    std::fs::write(&certs, generated.cert.pem())?;
+    write_private(&key, &generated.signing_key.serialize_pem())?;
+    eprintln!(
+        "wrote the certificate to {} and the key to {}",
+        certs.display(),
+        key.display()
+    );
+    print_fingerprint(options, Some(certs))
+}
+
+/// Prints the fingerprint of the first certificate in the file
+pub fn print_fingerprint(options: &ConfigOptions, certs: Option<PathBuf>) -> CommandResult {
+    let certs = match certs {
+        Some(certs) => certs,
+        None => configured_certs(options)?.0,
+    };
+    let fingerprint = Certs::read_fingerprint(&certs)?
+        .ok_or_else(|| format!("{} holds no certificate", certs.display()))?;
+    print_line(&fingerprint);
+    Ok(())
+}
+
+/// Migrates the persistent store
+// migrations will be able to fail once there is a store
+#[expect(clippy::unnecessary_wraps)]
+pub fn migrate() -> CommandResult {
+    eprintln!("the server has no persistent store yet, there is nothing to migrate");
+    Ok(())
+}
+
+/// Runs a `config` command
+pub fn config(command: ConfigCommand, config_path: Option<PathBuf>) -> CommandResult {
+    match command {
+        ConfigCommand::Init { path, force } => {
+            let path = path
+                .or(config_path)
+                .unwrap_or_else(|| PathBuf::from("config.toml"));
+            refuse_overwrite(&path, force)?;
+            std::fs::write(&path, default_config()?)?;
+            eprintln!("wrote the default config to {}", path.display());
+        }
+        ConfigCommand::Schema { output: Some(path) } => {
+            std::fs::write(&path, config_schema_string()?)?;
+            eprintln!("wrote the config schema to {}", path.display());
+        }
+        ConfigCommand::Schema { output: None } => print_line(config_schema_string()?.trim_end()),
+    }
+    Ok(())
+}
+
+/// The certificate and key paths of the config
+fn configured_certs(options: &ConfigOptions) -> Result<(PathBuf, PathBuf), ConfigError> {
+    let (config, _) = load_config(options)?;
+    Ok((config.network.certs, config.network.key))
+}
+
+/// Writes a file only the owner can read, for private keys
+fn write_private(path: &Path, contents: &str) -> io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path)?;
+    // the mode only applies to new files, not to overwritten ones
+    #[cfg(unix)]
+    file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+    file.write_all(contents.as_bytes())
+}
+
+fn refuse_overwrite(path: &Path, force: bool) -> io::Result<()> {
+    if path.exists() && !force {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!(
+                "{} already exists, use --force to overwrite it",
+                path.display()
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Prints the output of a command, messages go to stderr
+#[expect(clippy::print_stdout)]
+fn print_line(line: &str) {
+    println!("{line}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(dir: &Path) -> ConfigOptions {
+        ConfigOptions {
+            path: Some(dir.join("config.toml")),
+            ..ConfigOptions::default()
+        }
+    }
+
+    fn init(options: &ConfigOptions) -> CommandResult {
+        let command = ConfigCommand::Init {
+            path: None,
+            force: false,
+        };
+        config(command, options.path.clone())
+    }
+
+    #[test]
+    fn gen_cert_writes_a_private_key() -> CommandResult {
+        let dir = tempfile::tempdir()?;
+        let options = options(dir.path());
+        init(&options)?;
+        let certs = dir.path().join("certs.pem");
+        let key = dir.path().join("key.pem");
+        let gen_cert = |force| {
+            let hostnames = vec!["localhost".to_owned()];
+            gen_cert(
+                &options,
+                hostnames,
+                Some(certs.clone()),
+                Some(key.clone()),
+                force,
+            )
+        };
+
+        gen_cert(false)?;
+        assert!(Certs::read_from_file(&certs, &key).is_ok());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&key)?.permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // an existing certificate is only replaced with --force
+        assert!(gen_cert(false).is_err());
+        gen_cert(true)
+    }
+
+    #[test]
+    fn broken_config_is_reported() -> CommandResult {
+        let dir = tempfile::tempdir()?;
+        let options = options(dir.path());
+        std::fs::write(dir.path().join("config.toml"), "[network\n")?;
+
+        assert!(gen_cert(&options, vec!["localhost".to_owned()], None, None, false).is_err());
+        assert!(print_fingerprint(&options, None).is_err());
+        assert!(!dir.path().join("certs.pem").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn init_writes_a_loadable_config() -> CommandResult {
+        let dir = tempfile::tempdir()?;
+        let options = options(dir.path());
+
+        init(&options)?;
+        assert!(load_config(&options).is_ok());
+        assert!(init(&options).is_err());
+        Ok(())
+    }
+}
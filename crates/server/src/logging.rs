@@ -2,28 +2,131 @@
 // Copyright (C) 2025 Crypts of the Lost Team
 
 //! # Logging
-//! This module sets up the logging.
-
-use config::config::logging::{LogLevel, LoggingConfig, OutputFormat};
-use tracing::Level;
-use tracing_subscriber::FmtSubscriber;
-
-pub fn setup_logging(config: &LoggingConfig) {
-    let mut subscriber_builder = FmtSubscriber::builder();
-
-    // set max log level
-    subscriber_builder = match config.log_level {
-        LogLevel::Trace => subscriber_builder.with_max_level(Level::TRACE),
-        LogLevel::Debug => subscriber_builder.with_max_level(Level::DEBUG),
-        LogLevel::Info => subscriber_builder.with_max_level(Level::INFO),
-        LogLevel::Warn => subscriber_builder.with_max_level(Level::WARN),
-        LogLevel::Error => subscriber_builder.with_max_level(Level::ERROR),
+//! This module sets up the logging to the console and the configured files.
+
+mod rolling;
+
+use bevy::ecs::{event::EventReader, resource::Resource, system::Res};
+use config::{
+    Config, ConfigChanged,
+    config::logging::{LogLevel, LoggingConfig, OutputFormat},
+};
+use rolling::RollingFile;
+use std::{io, sync::Mutex};
+#[cfg(feature = "otlp")]
+use telemetry::otlp::{OtlpExporter, otlp};
+#[cfg(not(feature = "otlp"))]
+use tracing::warn;
+use tracing::{error, level_filters::LevelFilter};
+use tracing_subscriber::{
+    EnvFilter, Layer, Registry, fmt::MakeWriter, layer::SubscriberExt, reload,
+    util::SubscriberInitExt,
+};
+
+/// A formatting layer of one of the outputs
+type Output = Box<dyn Layer<Registry> + Send + Sync>;
+
+/// Changes the filter of the running subscriber
+#[derive(Debug, Resource)]
+pub struct LogFilterHandle(reload::Handle<EnvFilter, Registry>);
+
+/// Logs to stdout and every configured file, each in its own format, and
+/// exports the spans when a collector is configured. The exporter has to be
+/// kept until the server stops.
+///
+/// # Errors
+/// Returns an `io::Error` when a log file can't be opened or the exporter
+/// can't be started.
+pub fn setup_logging(
+    config: &LoggingConfig,
+) -> io::Result<(LogFilterHandle, Option<OtlpExporter>)> {
+    let (filter, handle) = reload::Layer::new(env_filter(config));
+
+    let mut outputs = vec![output(&config.output_format, io::stdout, true)];
+    for file in &config.files {
+        let writer = Mutex::new(RollingFile::open(file)?);
+        outputs.push(output(&file.format, writer, false));
+    }
+    #[cfg(feature = "otlp")]
+    let exporter = match &config.otlp {
+        Some(config) => {
+            let (layer, exporter) = otlp(config)?;
+            outputs.push(layer.boxed());
+            Some(exporter)
+        }
+        None => None,
     };
 
-    // set output format
-    match config.output_format {
-        OutputFormat::Default => subscriber_builder.init(),
-        OutputFormat::Pretty => subscriber_builder.pretty().init(),
-        OutputFormat::Json => subscriber_builder.json().init(),
+    tracing_subscriber::registry()
+        .with(outputs.with_filter(filter))
+        .init();
+
+    #[cfg(not(feature = "otlp"))]
+    let exporter = {
+        if config.otlp.is_some() {
+            warn!("`logging.otlp` is set, but the server was built without the `otlp` feature");
+        }
+        None
+    };
+    Ok((LogFilterHandle(handle), exporter))
+}
+
+/// Exports the spans when the server is built with the `otlp` feature
+#[cfg(not(feature = "otlp"))]
+#[derive(Debug)]
+pub enum OtlpExporter {}
+
+fn output<W>(format: &OutputFormat, writer: W, ansi: bool) -> Output
+where
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let layer = tracing_subscriber::fmt::layer()
+        .with_writer(writer)
+        .with_ansi(ansi);
+    match format {
+        OutputFormat::Default => layer.boxed(),
+        OutputFormat::Pretty => layer.pretty().boxed(),
+        OutputFormat::Json => layer.json().boxed(),
+    }
+}
+
+/// Applies a reloaded log level or filter
+pub fn reload_log_filter(
+    mut changes: EventReader<ConfigChanged>,
+    config: Res<Config>,
+    handle: Res<LogFilterHandle>,
+) {
+    if !changes.read().any(|change| {
+        change
+            .changed
+            .iter()
+            .any(|path| matches!(*path, "logging.log_level" | "logging.filter"))
+    }) {
+        return;
+    }
+    if let Err(e) = handle.0.reload(env_filter(&config.logging)) {
+        error!("Wasn't able to change the log filter: {e}");
+    }
+}
+
+/// The `log_level` for every target, overridden by the directives of
+/// `filter`, which were checked when the config was validated
+fn env_filter(config: &LoggingConfig) -> EnvFilter {
+    // a default directive only applies when no directives are given at all
+    let level = level_filter(&config.log_level);
+    let directives = config
+        .filter
+        .as_ref()
+        .map_or_else(|| level.to_string(), |filter| format!("{level},{filter}"));
+    EnvFilter::builder().parse_lossy(directives)
+}
+
+const fn level_filter(level: &LogLevel) -> LevelFilter {
+    match level {
+        LogLevel::Trace => LevelFilter::TRACE,
+        LogLevel::Debug => LevelFilter::DEBUG,
+        LogLevel::Info => LevelFilter::INFO,
+        LogLevel::Warn => LevelFilter::WARN,
+        LogLevel::Error => LevelFilter::ERROR,
     }
 }
@@ -0,0 +1,298 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # Rolling
+//! A log file that is rotated daily or once it reaches a size, deleting the
+//! oldest rotated files beyond the retention.
+
+use config::config::logging::{LogFileConfig, Rotation};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// The current file of a file output, written to by the fmt layer
+#[derive(Debug)]
+pub struct RollingFile {
+    directory: PathBuf,
+    name: String,
+    rotation: Rotation,
+    max_size: u64,
+    retention: usize,
+    file: File,
+    /// Bytes in the current file
+    size: u64,
+    /// Days since the epoch the current file was opened on
+    day: u64,
+    /// Returns the current day, replaced in tests
+    today: fn() -> u64,
+}
+
+impl RollingFile {
+    /// Opens the current file of the output, creating the directory if needed
+    ///
+    /// # Errors
+    /// Returns an `io::Error` when the directory or the file can't be created.
+    pub fn open(config: &LogFileConfig) -> io::Result<Self> {
+        Self::open_with(config, today)
+    }
+
+    fn open_with(config: &LogFileConfig, today: fn() -> u64) -> io::Result<Self> {
+        fs::create_dir_all(&config.directory)?;
+        let day = today();
+        let path = current_path(&config.directory, &config.name, config.rotation, day);
+        let file = append(&path)?;
+        let rolling = Self {
+            directory: config.directory.clone(),
+            name: config.name.clone(),
+            rotation: config.rotation,
+            max_size: config.max_size,
+            retention: config.retention,
+            size: file.metadata()?.len(),
+            file,
+            day,
+            today,
+        };
+        rolling.prune()?;
+        Ok(rolling)
+    }
+
+    /// Path of the file that is currently written to
+    fn path(&self) -> PathBuf {
+        current_path(&self.directory, &self.name, self.rotation, self.day)
+    }
+
+    /// Starts a new file if writing `len` bytes on `day` should go to one
+    fn roll(&mut self, len: usize, day: u64) -> io::Result<()> {
+        let rotate = match self.rotation {
+            Rotation::Daily => day != self.day,
+            Rotation::Size => self.size > 0 && self.size + len as u64 > self.max_size,
+        };
+        if !rotate {
+            return Ok(());
+        }
+
+        self.file.flush()?;
+        if self.rotation == Rotation::Size {
+            let next = self.rotated()?.last().map_or(1, |(index, _)| {
+                index.parse::<u64>().map_or(1, |index| index + 1)
+            });
+            fs::rename(
+                self.path(),
+                self.directory.join(format!("{}.{next}.log", self.name)),
+            )?;
+        }
+        self.day = day;
+        self.file = append(&self.path())?;
+        self.size = self.file.metadata()?.len();
+        self.prune()
+    }
+
+    /// The rotated files of this output, oldest first, with the part of the
+    /// name that tells them apart
+    fn rotated(&self) -> io::Result<Vec<(String, PathBuf)>> {
+        let current = self.path();
+        let prefix = format!("{}.", self.name);
+        let mut rotated = Vec::new();
+        for entry in fs::read_dir(&self.directory)? {
+            let path = entry?.path();
+            let Some(middle) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix(&prefix))
+                .and_then(|name| name.strip_suffix(".log"))
+            else {
+                continue;
+            };
+            // other outputs in the directory may share the prefix, such as
+            // `server.json` next to `server`
+            let rotated_name = match self.rotation {
+                Rotation::Daily => is_date(middle),
+                Rotation::Size => !middle.is_empty() && middle.bytes().all(|b| b.is_ascii_digit()),
+            };
+            if path != current && rotated_name {
+                rotated.push((middle.to_owned(), path));
+            }
+        }
+        // dates have a fixed width and numbers sort by their length first,
+        // so both end up in the order they were written in
+        rotated.sort_by(|(a, _), (b, _)| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+        Ok(rotated)
+    }
+
+    /// Deletes the oldest rotated files beyond the retention
+    fn prune(&self) -> io::Result<()> {
+        let rotated = self.rotated()?;
+        let excess = rotated.len().saturating_sub(self.retention);
+        for (_, path) in &rotated[..excess] {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for RollingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.roll(buf.len(), (self.today)())?;
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+fn append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+fn current_path(directory: &Path, name: &str, rotation: Rotation, day: u64) -> PathBuf {
+    match rotation {
+        Rotation::Daily => directory.join(format!("{name}.{}.log", date(day))),
+        Rotation::Size => directory.join(format!("{name}.log")),
+    }
+}
+
+/// Whether the text is a date formatted by [`date`]
+fn is_date(text: &str) -> bool {
+    text.len() == 10
+        && text.bytes().enumerate().all(|(index, b)| match index {
+            4 | 7 => b == b'-',
+            _ => b.is_ascii_digit(),
+        })
+}
+
+/// Days since the epoch, in UTC
+fn today() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs() / SECONDS_PER_DAY)
+}
+
+/// Formats days since the epoch as `YYYY-MM-DD`, using the civil calendar
+/// algorithm by Howard Hinnant
+fn date(days: u64) -> String {
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    fn names(directory: &Path) -> io::Result<Vec<String>> {
+        let mut names = fs::read_dir(directory)?
+            .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+            .collect::<io::Result<Vec<_>>>()?;
+        names.sort();
+        Ok(names)
+    }
+
+    #[test]
+    fn dates_are_civil() {
+        assert_eq!(date(0), "1970-01-01");
+        assert_eq!(date(11_016), "2000-02-29");
+        assert_eq!(date(20_454), "2026-01-01");
+    }
+
+    #[test]
+    fn size_rotation_keeps_the_retention() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut file = RollingFile::open(&LogFileConfig {
+            directory: dir.path().to_path_buf(),
+            rotation: Rotation::Size,
+            max_size: 8,
+            retention: 2,
+            ..LogFileConfig::default()
+        })?;
+        for _ in 0..12 {
+            file.write_all(b"line\n")?;
+        }
+
+        assert_eq!(
+            names(dir.path())?,
+            ["server.10.log", "server.11.log", "server.log"]
+        );
+        assert_eq!(fs::read(dir.path().join("server.log"))?, b"line\n");
+        Ok(())
+    }
+
+    #[test]
+    fn daily_rotation_starts_a_file_per_day() -> io::Result<()> {
+        static DAY: AtomicU64 = AtomicU64::new(20_454);
+
+        let dir = tempfile::tempdir()?;
+        let mut file = RollingFile::open_with(
+            &LogFileConfig {
+                directory: dir.path().to_path_buf(),
+                retention: 1,
+                ..LogFileConfig::default()
+            },
+            || DAY.load(Ordering::Relaxed),
+        )?;
+        for _ in 0..3 {
+            file.write_all(b"line\n")?;
+            DAY.fetch_add(1, Ordering::Relaxed);
+        }
+        file.write_all(b"line\n")?;
+
+        assert_eq!(
+            names(dir.path())?,
+            ["server.2026-01-03.log", "server.2026-01-04.log"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn outputs_sharing_a_prefix_keep_their_files() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let config = |name: &str, rotation| LogFileConfig {
+            directory: dir.path().to_path_buf(),
+            name: name.to_owned(),
+            rotation,
+            max_size: 8,
+            retention: 1,
+            ..LogFileConfig::default()
+        };
+        // a leftover daily file of the size output, and the other output
+        fs::write(dir.path().join("server.2026-01-01.log"), "old\n")?;
+        let mut json = RollingFile::open_with(&config("server.json", Rotation::Daily), || 20_454)?;
+        json.write_all(b"line\n")?;
+
+        let mut file = RollingFile::open(&config("server", Rotation::Size))?;
+        for _ in 0..3 {
+            file.write_all(b"line\n")?;
+        }
+
+        assert_eq!(
+            names(dir.path())?,
+            [
+                "server.2.log",
+                "server.2026-01-01.log",
+                "server.json.2026-01-01.log",
+                "server.log"
+            ]
+        );
+        Ok(())
+    }
+}
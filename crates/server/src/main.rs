@@ -6,33 +6,85 @@
 
 #![expect(clippy::multiple_crate_versions)]
 
+mod cli;
+mod commands;
 mod logging;
+mod tick;
 
 use bevy::prelude::*;
-use config::parse_config;
-use logging::setup_logging;
+use cli::{Cli, Command};
+use config::{ConfigOptions, ConfigReload, parse_config};
+use logging::{reload_log_filter, setup_logging};
 use network::Network;
 use protocol::Protocol;
-use std::time::Duration;
-
-const TPS: f64 = 16.;
+use std::process::ExitCode;
+use telemetry::Telemetry;
+use tick::TickPlugin;
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let config = parse_config()?;
+async fn main() -> ExitCode {
+    let cli = match Cli::parse_args() {
+        Ok(cli) => cli,
+        Err(e) => e.exit(),
+    };
+
+    let options = cli.config_options();
+    let result = match cli.command {
+        None | Some(Command::Run) => return run(options),
+        Some(Command::CheckConfig) => commands::check_config(&options),
+        Some(Command::GenCert {
+            hostnames,
+            certs,
+            key,
+            force,
+        }) => commands::gen_cert(&options, hostnames, certs, key, force),
+        Some(Command::PrintFingerprint { certs }) => commands::print_fingerprint(&options, certs),
+        Some(Command::Migrate) => commands::migrate(),
+        Some(Command::Config(command)) => commands::config(command, cli.config),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Runs the server until it is stopped
+fn run(options: ConfigOptions) -> ExitCode {
+    let config = match parse_config(&options) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
 
-    setup_logging(&config.logging);
+    let (log_filter, _exporter) = match setup_logging(&config.logging) {
+        Ok(logging) => logging,
+        Err(e) => {
+            eprintln!("Wasn't able to set up logging: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
 
-    App::new()
-        .add_plugins(
-            MinimalPlugins.set(bevy::app::ScheduleRunnerPlugin::run_loop(
-                Duration::from_secs_f64(1. / TPS),
-            )),
-        )
+    let timestep = config.tick.timestep();
+    let exit = App::new()
+        .add_plugins(MinimalPlugins.set(bevy::app::ScheduleRunnerPlugin::run_loop(timestep)))
         .add_plugins(Protocol)
-        .add_plugins(Network)
+        .add_plugins(TickPlugin::new(&config.tick))
+        .add_plugins(Network::new(env!("CARGO_PKG_VERSION")))
+        .add_plugins(Telemetry::new(timestep))
+        .add_plugins(ConfigReload::new(options))
         .insert_resource(config)
+        .insert_resource(log_filter)
+        .add_systems(PreUpdate, reload_log_filter)
         .run();
 
-    Ok(())
+    if exit.is_error() {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
 }
@@ -0,0 +1,214 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # Tick
+//! Runs the simulation in fixed ticks. Gameplay systems belong in
+//! `FixedUpdate`, which runs once for every tick that passed since the last
+//! update, so a slow tick is caught up on by running the next ones back to
+//! back.
+
+use bevy::{
+    app::{App, FixedFirst, FixedLast, FixedMainScheduleOrder, Plugin, PreUpdate},
+    ecs::{
+        resource::Resource,
+        schedule::ScheduleLabel,
+        system::{NonSendMut, Res, ResMut},
+    },
+    time::{Fixed, Real, Time, Virtual},
+};
+use config::config::tick::TickConfig;
+use protocol::ServerTick;
+use std::time::{Duration, Instant};
+use telemetry::metrics::TickMetrics;
+use tracing::{debug_span, span::EnteredSpan, warn};
+
+/// Plugin running `FixedUpdate` at the configured tick rate and advancing
+/// the [`ServerTick`], which the `Protocol` plugin adds
+#[derive(Debug)]
+pub struct TickPlugin {
+    timestep: Duration,
+    max_catch_up: u32,
+}
+
+impl TickPlugin {
+    /// Creates the plugin from the `tick` section of the config
+    #[must_use]
+    pub fn new(config: &TickConfig) -> Self {
+        Self {
+            timestep: config.timestep(),
+            max_catch_up: config.max_catch_up.max(1),
+        }
+    }
+}
+
+impl Plugin for TickPlugin {
+    fn build(&self, app: &mut App) {
+        // virtual time advancing more than this in one update is dropped,
+        // which limits the ticks run back to back
+        let mut virtual_time = Time::<Virtual>::default();
+        virtual_time.set_max_delta(self.timestep * self.max_catch_up);
+
+        app.insert_resource(Time::<Fixed>::from_duration(self.timestep))
+            .insert_resource(virtual_time)
+            .insert_resource(TickStart(Instant::now()))
+            .insert_non_send_resource(TickSpan(None))
+            .init_schedule(BeginTick)
+            .init_schedule(EndTick)
+            .add_systems(BeginTick, start_tick)
+            .add_systems(EndTick, finish_tick)
+            .add_systems(PreUpdate, warn_dropped_time);
+
+        let mut order = app.world_mut().resource_mut::<FixedMainScheduleOrder>();
+        order.insert_before(FixedFirst, BeginTick);
+        order.insert_after(FixedLast, EndTick);
+    }
+}
+
+/// Runs first in every tick, before `FixedFirst`. Starts the tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ScheduleLabel)]
+pub struct BeginTick;
+
+/// Runs last in every tick, after `FixedLast`. Finishes the tick, so the
+/// measured duration covers every system of the tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ScheduleLabel)]
+pub struct EndTick;
+
+/// When the current tick started
+#[derive(Debug, Resource)]
+struct TickStart(Instant);
+
+/// The span of the current tick, exported with the `server::tick=debug`
+/// filter. It stays entered while the tick runs, so the spans and logs of
+/// the systems of the tick are below it. This relies on the systems running
+/// on the main thread, which the single threaded executor does.
+#[derive(Debug)]
+struct TickSpan(Option<EnteredSpan>);
+
+fn start_tick(
+    mut tick: ResMut<ServerTick>,
+    mut start: ResMut<TickStart>,
+    mut span: NonSendMut<TickSpan>,
+) {
+    tick.advance();
+    start.0 = Instant::now();
+    span.0 = Some(debug_span!("tick", tick = tick.0).entered());
+}
+
+fn finish_tick(
+    tick: Res<ServerTick>,
+    start: Res<TickStart>,
+    mut span: NonSendMut<TickSpan>,
+    fixed: Res<Time<Fixed>>,
+    metrics: Option<Res<TickMetrics>>,
+) {
+    let took = start.0.elapsed();
+    // exits and closes the span of the tick
+    span.0 = None;
+    if let Some(metrics) = metrics {
+        metrics.record(took);
+    }
+    let timestep = fixed.timestep();
+    if took > timestep {
+        warn!(
+            "tick {} took {took:?}, longer than the timestep of {timestep:?}",
+            tick.0
+        );
+    }
+}
+
+/// Warns when the server fell behind further than it is allowed to catch up on
+fn warn_dropped_time(real: Res<Time<Real>>, virtual_time: Res<Time<Virtual>>) {
+    let dropped = real.delta().saturating_sub(virtual_time.delta());
+    if !virtual_time.is_paused() && !dropped.is_zero() {
+        warn!("server fell behind, skipping {dropped:?} of simulation");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::{
+        app::FixedUpdate,
+        time::{TimePlugin, TimeUpdateStrategy},
+    };
+    use protocol::Protocol;
+
+    /// An app ticking 16 times per second, advancing time by `elapsed`
+    /// on every update
+    fn app(elapsed: Duration) -> App {
+        let config = TickConfig::default();
+        let mut app = App::new();
+        app.add_plugins((TimePlugin, Protocol, TickPlugin::new(&config)))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(elapsed));
+        app
+    }
+
+    fn tick(app: &App) -> u64 {
+        app.world().resource::<ServerTick>().0
+    }
+
+    #[test]
+    fn advances_once_per_timestep() {
+        let timestep = TickConfig::default().timestep();
+        let mut app = app(timestep);
+
+        app.update();
+        let first = tick(&app);
+        for _ in 0..5 {
+            app.update();
+        }
+        assert_eq!(tick(&app), first + 5);
+    }
+
+    #[test]
+    fn catches_up_on_at_most_max_catch_up_ticks() {
+        let config = TickConfig::default();
+        let mut app = app(config.timestep() * 10);
+
+        app.update();
+        let first = tick(&app);
+        app.update();
+        assert_eq!(tick(&app), first + u64::from(config.max_catch_up));
+    }
+
+    #[test]
+    fn measures_the_entire_tick() {
+        let timestep = TickConfig::default().timestep();
+        let metrics = TickMetrics::new(timestep);
+        let pause = || std::thread::sleep(Duration::from_millis(2));
+        let mut app = app(timestep);
+        app.insert_resource(metrics.clone())
+            .add_systems(FixedFirst, pause)
+            .add_systems(FixedLast, pause);
+
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(metrics.ticks(), tick(&app));
+        assert!(metrics.last() >= Duration::from_millis(4));
+    }
+
+    #[derive(Default, Resource)]
+    struct CurrentSpan(Option<&'static str>);
+
+    #[test]
+    fn systems_run_in_the_tick_span() {
+        tracing::subscriber::with_default(tracing_subscriber::registry(), || {
+            let mut app = app(TickConfig::default().timestep());
+            app.init_resource::<CurrentSpan>().add_systems(
+                FixedUpdate,
+                |mut current: ResMut<CurrentSpan>| {
+                    current.0 = tracing::Span::current()
+                        .metadata()
+                        .map(tracing::Metadata::name);
+                },
+            );
+
+            for _ in 0..3 {
+                app.update();
+            }
+            assert_eq!(app.world().resource::<CurrentSpan>().0, Some("tick"));
+            assert!(tracing::Span::current().is_none());
+        });
+    }
+}
@@ -0,0 +1,100 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # Health
+//! Liveness and readiness checks for orchestrators and process supervisors.
+//!
+//! The server is healthy as long as ticks keep finishing and the network
+//! handler didn't stop, and ready once the handler accepts connections.
+
+use crate::{http::Response, metrics::TickMetrics};
+use network::{NetworkState, NetworkStatus};
+use std::time::Duration;
+
+/// Checks the health of the server from outside the bevy loop
+#[derive(Debug, Clone)]
+pub struct Health {
+    ticks: TickMetrics,
+    network: Option<NetworkStatus>,
+    stall_timeout: Duration,
+}
+
+impl Health {
+    /// Checks the ticks and, when the network plugin is used, the network handler
+    #[must_use]
+    pub const fn new(
+        ticks: TickMetrics,
+        network: Option<NetworkStatus>,
+        stall_timeout: Duration,
+    ) -> Self {
+        Self {
+            ticks,
+            network,
+            stall_timeout,
+        }
+    }
+
+    /// Whether the server is alive, returns the reason when it isn't
+    ///
+    /// # Errors
+    /// Returns why the server is unhealthy.
+    pub fn live(&self) -> Result<(), String> {
+        let since = self.ticks.since_last_tick();
+        if since > self.stall_timeout {
+            return Err(format!("no tick finished for {:.1}s", since.as_secs_f64()));
+        }
+
+        if let Some(NetworkState::Stopped(reason)) = self.network.as_ref().map(NetworkStatus::state)
+        {
+            return Err(format!("the network handler stopped: {reason}"));
+        }
+
+        Ok(())
+    }
+
+    /// Whether the server is alive and accepts connections, returns the
+    /// reason when it isn't
+    ///
+    /// # Errors
+    /// Returns why the server isn't ready.
+    pub fn ready(&self) -> Result<(), String> {
+        self.live()?;
+        match self.network.as_ref().map(NetworkStatus::state) {
+            Some(NetworkState::Starting) => Err("the network handler isn't bound yet".to_owned()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Answers a health check, `200` when it passes and `503` with the reason otherwise
+#[must_use]
+pub fn respond(check: Result<(), String>) -> Response {
+    match check {
+        Ok(()) => Response::text(200, "ok"),
+        Err(reason) => Response::text(503, &reason),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stalls_and_starting_network_fail() {
+        let ticks = TickMetrics::new(Duration::from_millis(50));
+        let health = Health::new(
+            ticks.clone(),
+            Some(NetworkStatus::default()),
+            Duration::ZERO,
+        );
+        assert!(health.live().is_err());
+
+        let health = Health::new(
+            ticks,
+            Some(NetworkStatus::default()),
+            Duration::from_secs(30),
+        );
+        assert_eq!(health.live(), Ok(()));
+        assert!(health.ready().is_err());
+    }
+}
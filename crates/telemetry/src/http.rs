@@ -0,0 +1,192 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # HTTP
+//! A tiny HTTP/1.1 server, just enough to answer the `GET` requests of
+//! scrapers and health checks on a local address.
+
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+use tracing::{debug, warn};
+
+/// Largest request head that is read, the rest of the request is ignored
+const MAX_REQUEST_SIZE: usize = 8 * 1024;
+
+/// Time a client gets to send its request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Pause after a failed accept, errors such as running out of file
+/// descriptors usually last a while
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Answers the request for the given path
+pub type Handler = Arc<dyn Fn(&str) -> Response + Send + Sync>;
+
+/// A response to a request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    /// Status code
+    pub status: u16,
+    /// Value of the `Content-Type` header
+    pub content_type: &'static str,
+    /// The body
+    pub body: String,
+}
+
+impl Response {
+    /// A `200 OK` response with the given body
+    #[must_use]
+    pub const fn ok(content_type: &'static str, body: String) -> Self {
+        Self {
+            status: 200,
+            content_type,
+            body,
+        }
+    }
+
+    /// A plain text response with the given status
+    #[must_use]
+    pub fn text(status: u16, body: &str) -> Self {
+        Self {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            body: format!("{body}\n"),
+        }
+    }
+
+    /// A `404 Not Found` response
+    #[must_use]
+    pub fn not_found() -> Self {
+        Self::text(404, "not found")
+    }
+
+    const fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            503 => "Service Unavailable",
+            _ => "",
+        }
+    }
+}
+
+/// Binds a listener, so binding errors are reported before serving
+///
+/// # Errors
+/// Returns an `io::Error` when the address can't be bound.
+pub fn bind(addr: SocketAddr) -> std::io::Result<TcpListener> {
+    let listener = std::net::TcpListener::bind(addr)?;
+    listener.set_nonblocking(true)?;
+    TcpListener::from_std(listener)
+}
+
+/// Answers requests on the listener until the task is dropped
+pub async fn serve(listener: TcpListener, handler: Handler) {
+    loop {
+        let (stream, addr) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                warn!("error accepting HTTP connection: {e}");
+                tokio::time::sleep(ACCEPT_BACKOFF).await;
+                continue;
+            }
+        };
+        debug!("HTTP connection from {addr}");
+        tokio::spawn(answer(stream, handler.clone()));
+    }
+}
+
+async fn answer(mut stream: TcpStream, handler: Handler) {
+    let response = match tokio::time::timeout(REQUEST_TIMEOUT, read_head(&mut stream)).await {
+        Ok(Some(head)) => respond(&head, &handler),
+        Ok(None) => Response::text(400, "bad request"),
+        Err(_) => return,
+    };
+
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        response.reason(),
+        response.content_type,
+        response.body.len()
+    );
+    if let Err(e) = stream.write_all(head.as_bytes()).await {
+        debug!("error writing HTTP response: {e}");
+        return;
+    }
+    if let Err(e) = stream.write_all(response.body.as_bytes()).await {
+        debug!("error writing HTTP response: {e}");
+        return;
+    }
+    let _ = stream.shutdown().await;
+}
+
+/// Reads until the end of the request head
+async fn read_head(stream: &mut TcpStream) -> Option<String> {
+    let mut head = Vec::new();
+    let mut buf = [0; 1024];
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        let read = stream.read(&mut buf).await.ok()?;
+        if read == 0 || head.len() + read > MAX_REQUEST_SIZE {
+            return None;
+        }
+        head.extend_from_slice(&buf[..read]);
+    }
+    String::from_utf8(head).ok()
+}
+
+fn respond(head: &str, handler: &Handler) -> Response {
+    let mut request_line = head.lines().next().unwrap_or_default().split(' ');
+    let (Some(method), Some(target)) = (request_line.next(), request_line.next()) else {
+        return Response::text(400, "bad request");
+    };
+    if method != "GET" {
+        return Response::text(405, "method not allowed");
+    }
+
+    let path = target.split('?').next().unwrap_or(target);
+    handler(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    #[tokio::test]
+    async fn answers_requests() -> std::io::Result<()> {
+        let listener = bind((Ipv4Addr::LOCALHOST, 0).into())?;
+        let addr = listener.local_addr()?;
+        let handler: Handler = Arc::new(|path| match path {
+            "/hello" => Response::ok("text/plain", "hello".to_owned()),
+            _ => Response::not_found(),
+        });
+        let server = tokio::spawn(serve(listener, handler));
+
+        let get = |request: &'static str| async move {
+            let mut stream = TcpStream::connect(addr).await?;
+            stream.write_all(request.as_bytes()).await?;
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await?;
+            std::io::Result::Ok(response)
+        };
+
+        let response = get("GET /hello?x=1 HTTP/1.1\r\nHost: localhost\r\n\r\n").await?;
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.ends_with("\r\n\r\nhello"));
+
+        let response = get("GET /missing HTTP/1.1\r\n\r\n").await?;
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+
+        let response = get("POST /hello HTTP/1.1\r\n\r\n").await?;
+        assert!(response.starts_with("HTTP/1.1 405 Method Not Allowed\r\n"));
+
+        server.abort();
+        Ok(())
+    }
+}
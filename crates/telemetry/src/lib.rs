@@ -0,0 +1,123 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # Telemetry
+//! Endpoints for operators to monitor the server. Serves Prometheus metrics
+//! about the network and the ticks, and health checks, on local HTTP
+//! addresses configured in the `telemetry` section of the config. With the
+//! `otlp` feature, spans can be exported to an OpenTelemetry collector, see
+//! `otlp`.
+
+#![expect(clippy::multiple_crate_versions)]
+
+pub mod health;
+pub mod http;
+pub mod metrics;
+#[cfg(feature = "otlp")]
+pub mod otlp;
+
+use bevy::{
+    app::{App, Plugin, PostStartup},
+    ecs::{
+        resource::Resource,
+        system::{Res, ResMut},
+    },
+};
+use config::Config;
+use health::Health;
+use http::Response;
+use metrics::{TickMetrics, render};
+use network::{NetworkMetrics, NetworkStatus};
+use std::{collections::BTreeSet, net::SocketAddr, sync::Arc, time::Duration};
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+/// Plugin counting the fixed ticks and serving the metrics and health checks
+#[derive(Debug)]
+pub struct Telemetry {
+    /// The duration a tick should take at most
+    pub tick: Duration,
+}
+
+impl Telemetry {
+    /// Measures ticks against the given target duration
+    #[must_use]
+    pub const fn new(tick: Duration) -> Self {
+        Self { tick }
+    }
+}
+
+impl Plugin for Telemetry {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(TickMetrics::new(self.tick))
+            .init_resource::<Endpoints>()
+            // after the network plugin inserted its metrics
+            .add_systems(PostStartup, serve_endpoints);
+    }
+}
+
+/// The tasks serving the endpoints, stopped when the resource is dropped
+#[derive(Debug, Default, Resource)]
+struct Endpoints {
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl Drop for Endpoints {
+    fn drop(&mut self) {
+        self.tasks.iter().for_each(JoinHandle::abort);
+    }
+}
+
+fn serve_endpoints(
+    config: Res<Config>,
+    ticks: Res<TickMetrics>,
+    network: Option<Res<NetworkMetrics>>,
+    status: Option<Res<NetworkStatus>>,
+    mut endpoints: ResMut<Endpoints>,
+) {
+    let telemetry = &config.telemetry;
+    let network = network.map(|network| network.clone());
+    let health = Health::new(
+        ticks.clone(),
+        status.map(|status| status.clone()),
+        Duration::from_secs_f64(telemetry.stall_timeout.max(0.)),
+    );
+
+    let addresses: BTreeSet<SocketAddr> = [telemetry.metrics, telemetry.health]
+        .into_iter()
+        .flatten()
+        .collect();
+    for addr in addresses {
+        let listener = match http::bind(addr) {
+            Ok(listener) => listener,
+            Err(e) => {
+                error!("Wasn't able to serve telemetry on {addr}: {e}");
+                continue;
+            }
+        };
+
+        let metrics = telemetry.metrics == Some(addr);
+        let checks = telemetry.health == Some(addr);
+        if metrics {
+            info!("serving metrics on http://{addr}/metrics");
+        }
+        if checks {
+            info!("serving health checks on http://{addr}/health and http://{addr}/ready");
+        }
+
+        let ticks = ticks.clone();
+        let network = network.clone();
+        let health = health.clone();
+        let handler: http::Handler = Arc::new(move |path| match path {
+            "/metrics" if metrics => {
+                Response::ok(metrics::CONTENT_TYPE, render(network.as_ref(), &ticks))
+            }
+            "/health" if checks => health::respond(health.live()),
+            "/ready" if checks => health::respond(health.ready()),
+            _ => Response::not_found(),
+        });
+        endpoints
+            .tasks
+            .push(tokio::spawn(http::serve(listener, handler)));
+    }
+}
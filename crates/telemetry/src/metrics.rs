@@ -0,0 +1,346 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # Metrics
+//! Counts the duration of every tick, as measured by the server, and renders
+//! all counters in the Prometheus text format.
+
+use bevy::ecs::resource::Resource;
+use network::NetworkMetrics;
+use std::{
+    fmt::Write,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+/// Content type of the Prometheus text format
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Upper bounds of the tick duration histogram, in seconds
+const TICK_BUCKETS: [f64; 10] = [0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.];
+
+/// Counters about the ticks of the server, inserted as a resource by the
+/// `Telemetry` plugin and fed by the tick timer of the server
+#[derive(Debug, Clone, Resource)]
+pub struct TickMetrics {
+    inner: Arc<TickCounters>,
+}
+
+#[derive(Debug)]
+struct TickCounters {
+    /// The duration a tick should take at most
+    target: Duration,
+    ticks: AtomicU64,
+    overruns: AtomicU64,
+    /// Nanoseconds spent in all ticks
+    total: AtomicU64,
+    /// Nanoseconds spent in the last tick
+    last: AtomicU64,
+    /// When the counters were created
+    created: Instant,
+    /// Nanoseconds since `created` when the last tick finished
+    finished: AtomicU64,
+    buckets: [AtomicU64; TICK_BUCKETS.len()],
+}
+
+impl TickMetrics {
+    /// Creates the counters for ticks that should take at most `target`
+    #[must_use]
+    pub fn new(target: Duration) -> Self {
+        Self {
+            inner: Arc::new(TickCounters {
+                target,
+                ticks: AtomicU64::default(),
+                overruns: AtomicU64::default(),
+                total: AtomicU64::default(),
+                last: AtomicU64::default(),
+                created: Instant::now(),
+                finished: AtomicU64::default(),
+                buckets: Default::default(),
+            }),
+        }
+    }
+
+    /// Records a finished tick
+    pub fn record(&self, duration: Duration) {
+        let counters = &self.inner;
+        let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+
+        counters.ticks.fetch_add(1, Ordering::Relaxed);
+        counters.total.fetch_add(nanos, Ordering::Relaxed);
+        counters.last.store(nanos, Ordering::Relaxed);
+        counters.finished.store(
+            u64::try_from(counters.created.elapsed().as_nanos()).unwrap_or(u64::MAX),
+            Ordering::Relaxed,
+        );
+        if duration > counters.target {
+            counters.overruns.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let seconds = duration.as_secs_f64();
+        if let Some(bucket) = TICK_BUCKETS
+            .iter()
+            .position(|bound| seconds <= *bound)
+            .and_then(|index| counters.buckets.get(index))
+        {
+            bucket.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// The duration a tick should take at most
+    #[must_use]
+    pub fn target(&self) -> Duration {
+        self.inner.target
+    }
+
+    /// Amount of finished ticks
+    #[must_use]
+    pub fn ticks(&self) -> u64 {
+        self.inner.ticks.load(Ordering::Relaxed)
+    }
+
+    /// Amount of ticks that took longer than the target
+    #[must_use]
+    pub fn overruns(&self) -> u64 {
+        self.inner.overruns.load(Ordering::Relaxed)
+    }
+
+    /// Duration of the last tick
+    #[must_use]
+    pub fn last(&self) -> Duration {
+        Duration::from_nanos(self.inner.last.load(Ordering::Relaxed))
+    }
+
+    /// Time since the last tick finished, or since the start before the first tick
+    #[must_use]
+    pub fn since_last_tick(&self) -> Duration {
+        let finished = Duration::from_nanos(self.inner.finished.load(Ordering::Relaxed));
+        self.inner.created.elapsed().saturating_sub(finished)
+    }
+}
+
+/// Renders the metrics in the Prometheus text format
+#[must_use]
+pub fn render(network: Option<&NetworkMetrics>, ticks: &TickMetrics) -> String {
+    let mut out = Exposition::default();
+
+    let counters = &ticks.inner;
+    out.gauge(
+        "cotl_tick_target_seconds",
+        "Duration a tick should take at most, one over the rate",
+        counters.target.as_secs_f64(),
+    );
+    out.counter("cotl_ticks_total", "Finished ticks", ticks.ticks());
+    out.counter(
+        "cotl_tick_overruns_total",
+        "Ticks that took longer than the target",
+        ticks.overruns(),
+    );
+    out.gauge(
+        "cotl_tick_last_duration_seconds",
+        "Duration of the last tick",
+        ticks.last().as_secs_f64(),
+    );
+    out.header(
+        "cotl_tick_duration_seconds",
+        "Duration of the ticks",
+        "histogram",
+    );
+    let mut cumulative = 0;
+    for (bound, bucket) in TICK_BUCKETS.iter().zip(&counters.buckets) {
+        cumulative += bucket.load(Ordering::Relaxed);
+        out.sample(
+            "cotl_tick_duration_seconds_bucket",
+            &[("le", &bound.to_string())],
+            cumulative,
+        );
+    }
+    out.sample(
+        "cotl_tick_duration_seconds_bucket",
+        &[("le", "+Inf")],
+        ticks.ticks(),
+    );
+    out.sample(
+        "cotl_tick_duration_seconds_sum",
+        &[],
+        Duration::from_nanos(counters.total.load(Ordering::Relaxed)).as_secs_f64(),
+    );
+    out.sample("cotl_tick_duration_seconds_count", &[], ticks.ticks());
+
+    if let Some(network) = network {
+        render_network(&mut out, network);
+    }
+
+    out.text
+}
+
+fn render_network(out: &mut Exposition, network: &NetworkMetrics) {
+    let handler = &network.handler;
+    out.gauge(
+        "cotl_connections",
+        "Open connections",
+        handler.connections(),
+    );
+    out.counter(
+        "cotl_connections_accepted_total",
+        "Accepted connections",
+        handler.accepted_connections(),
+    );
+    out.counter(
+        "cotl_connections_rejected_total",
+        "Connections that failed the handshake or didn't open a stream",
+        handler.rejected_connections(),
+    );
+    out.counter("cotl_joins_total", "Received joins", handler.joins());
+
+    let inbound = handler.inbound();
+    let outbound = handler.outbound();
+    let messages = || {
+        inbound
+            .iter()
+            .map(|(name, counts)| ("inbound", name, counts))
+            .chain(
+                outbound
+                    .iter()
+                    .map(|(name, counts)| ("outbound", name, counts)),
+            )
+    };
+    out.header(
+        "cotl_messages_total",
+        "Messages received and sent, by type",
+        "counter",
+    );
+    for (direction, name, (count, _)) in messages() {
+        out.sample(
+            "cotl_messages_total",
+            &[("direction", direction), ("type", name)],
+            *count,
+        );
+    }
+    out.header(
+        "cotl_message_bytes_total",
+        "Bytes received and sent including the length prefix, by type",
+        "counter",
+    );
+    for (direction, name, (_, bytes)) in messages() {
+        out.sample(
+            "cotl_message_bytes_total",
+            &[("direction", direction), ("type", name)],
+            *bytes,
+        );
+    }
+
+    out.header(
+        "cotl_serialization_failures_total",
+        "Frames that couldn't be decoded and events that couldn't be encoded",
+        "counter",
+    );
+    out.sample(
+        "cotl_serialization_failures_total",
+        &[("direction", "inbound")],
+        handler.deserialize_failures(),
+    );
+    out.sample(
+        "cotl_serialization_failures_total",
+        &[("direction", "outbound")],
+        handler.serialize_failures(),
+    );
+
+    out.header(
+        "cotl_queue_depth",
+        "Messages waiting in the queues between the network and the game",
+        "gauge",
+    );
+    for (queue, depth) in [
+        ("inbound", handler.inbound_queue()),
+        ("outbound", handler.outbound_queue()),
+        ("broadcast", handler.broadcast_queue()),
+    ] {
+        out.sample("cotl_queue_depth", &[("queue", queue)], depth);
+    }
+    out.counter(
+        "cotl_broadcast_lagged_events_total",
+        "Events skipped by connections that fell behind",
+        handler.lagged_events(),
+    );
+
+    render_compression(out, network);
+}
+
+fn render_compression(out: &mut Exposition, network: &NetworkMetrics) {
+    let compression = &network.compression;
+    out.counter(
+        "cotl_compressed_frames_total",
+        "Frames sent compressed",
+        compression.compressed_frames(),
+    );
+    out.counter(
+        "cotl_compression_input_bytes_total",
+        "Size of the compressed frames before compression",
+        compression.uncompressed_bytes(),
+    );
+    out.counter(
+        "cotl_compression_output_bytes_total",
+        "Size of the compressed frames after compression",
+        compression.compressed_bytes(),
+    );
+}
+
+/// Writes metrics in the Prometheus text format
+#[derive(Debug, Default)]
+struct Exposition {
+    text: String,
+}
+
+impl Exposition {
+    fn header(&mut self, name: &str, help: &str, kind: &str) {
+        let _ = writeln!(self.text, "# HELP {name} {help}");
+        let _ = writeln!(self.text, "# TYPE {name} {kind}");
+    }
+
+    fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: impl ToString) {
+        self.text.push_str(name);
+        if !labels.is_empty() {
+            let labels = labels
+                .iter()
+                .map(|(key, value)| format!("{key}=\"{value}\""))
+                .collect::<Vec<_>>()
+                .join(",");
+            let _ = write!(self.text, "{{{labels}}}");
+        }
+        let _ = writeln!(self.text, " {}", value.to_string());
+    }
+
+    fn counter(&mut self, name: &str, help: &str, value: u64) {
+        self.header(name, help, "counter");
+        self.sample(name, &[], value);
+    }
+
+    fn gauge(&mut self, name: &str, help: &str, value: impl ToString) {
+        self.header(name, help, "gauge");
+        self.sample(name, &[], value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tick_histogram_is_cumulative() {
+        let ticks = TickMetrics::new(Duration::from_millis(50));
+        ticks.record(Duration::from_millis(3));
+        ticks.record(Duration::from_millis(20));
+        ticks.record(Duration::from_millis(70));
+
+        let text = render(None, &ticks);
+        assert!(text.contains("cotl_tick_overruns_total 1\n"));
+        assert!(text.contains("cotl_tick_duration_seconds_bucket{le=\"0.005\"} 1\n"));
+        assert!(text.contains("cotl_tick_duration_seconds_bucket{le=\"0.05\"} 2\n"));
+        assert!(text.contains("cotl_tick_duration_seconds_bucket{le=\"+Inf\"} 3\n"));
+        assert!(text.contains("cotl_tick_duration_seconds_count 3\n"));
+    }
+}
@@ -0,0 +1,144 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # OTLP
+//! Exports spans to an OpenTelemetry collector over OTLP/HTTP, using
+//! `tracing-opentelemetry`, so the connection, command and tick spans show
+//! up in tools such as Jaeger or Tempo. Built with the `otlp` feature.
+
+use config::config::logging::OtlpConfig;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::{
+    Resource,
+    trace::{Sampler, SdkTracer, SdkTracerProvider},
+};
+use std::io;
+use tracing::{Subscriber, warn};
+use tracing_opentelemetry::OpenTelemetryLayer;
+use tracing_subscriber::registry::LookupSpan;
+
+/// Layer recording the spans for the [`OtlpExporter`]
+pub type OtlpLayer<S> = OpenTelemetryLayer<S, SdkTracer>;
+
+/// Creates the layer recording the spans and starts exporting them in
+/// batches, over TLS for `https://` endpoints.
+///
+/// Whether a trace is exported is decided at its root span, by the sampling
+/// ratio, and followed by every span below it.
+///
+/// # Errors
+/// Returns an `io::Error` when the exporter can't be built.
+pub fn otlp<S>(config: &OtlpConfig) -> io::Result<(OtlpLayer<S>, OtlpExporter)>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_endpoint(format!(
+            "{}/v1/traces",
+            config.endpoint.trim_end_matches('/')
+        ))
+        .build()
+        .map_err(io::Error::other)?;
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+            config.sampling_ratio,
+        ))))
+        .with_resource(
+            Resource::builder_empty()
+                .with_service_name(config.service_name.clone())
+                .build(),
+        )
+        .build();
+
+    let layer = tracing_opentelemetry::layer().with_tracer(provider.tracer("crypts-of-the-lost"));
+    Ok((layer, OtlpExporter { provider }))
+}
+
+/// Exports the spans in the background, exporting the remaining ones when
+/// dropped
+#[derive(Debug)]
+pub struct OtlpExporter {
+    provider: SdkTracerProvider,
+}
+
+impl Drop for OtlpExporter {
+    fn drop(&mut self) {
+        if let Err(e) = self.provider.shutdown() {
+            warn!("Wasn't able to export the remaining spans: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io::{Read, Write},
+        net::{Ipv4Addr, TcpListener},
+        thread,
+    };
+    use tracing_subscriber::layer::SubscriberExt;
+
+    /// Reads one request and answers it like a collector would
+    fn collect(listener: &TcpListener) -> io::Result<(String, Vec<u8>)> {
+        let (mut stream, _) = listener.accept()?;
+        let mut request = Vec::new();
+        let mut buf = [0; 4096];
+        let (head, length) = loop {
+            let read = stream.read(&mut buf)?;
+            request.extend_from_slice(&buf[..read]);
+            let text = String::from_utf8_lossy(&request);
+            if let Some((head, _)) = text.split_once("\r\n\r\n") {
+                let length = head
+                    .lines()
+                    .filter_map(|line| line.split_once(": "))
+                    .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+                    .and_then(|(_, length)| length.parse::<usize>().ok())
+                    .unwrap_or_default();
+                break (head.to_owned(), length);
+            }
+        };
+        while request.len() < head.len() + 4 + length {
+            let read = stream.read(&mut buf)?;
+            request.extend_from_slice(&buf[..read]);
+        }
+        stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")?;
+
+        let body = request.split_off(head.len() + 4);
+        Ok((head, body))
+    }
+
+    // the server sets up the exporter inside its tokio runtime
+    #[tokio::test]
+    async fn spans_are_posted_to_the_collector() -> Result<(), Box<dyn std::error::Error>> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        let (layer, exporter) = otlp(&OtlpConfig {
+            endpoint: format!("http://{}/otel/", listener.local_addr()?),
+            service_name: "test-service".to_owned(),
+            ..OtlpConfig::default()
+        })?;
+        let collector = thread::spawn(move || collect(&listener));
+
+        let subscriber = tracing_subscriber::registry().with(layer);
+        tracing::subscriber::with_default(subscriber, || {
+            let _connection = tracing::info_span!("connection", session = 7).entered();
+            tracing::info_span!("command").in_scope(|| tracing::info!("handled"));
+        });
+        drop(exporter);
+
+        let (head, body) = collector.join().map_err(|_| "the collector panicked")??;
+        assert!(
+            head.starts_with("POST /otel/v1/traces HTTP/1.1\r\n"),
+            "{head}"
+        );
+
+        let contains = |text: &str| body.windows(text.len()).any(|part| part == text.as_bytes());
+        assert!(contains("test-service"));
+        assert!(contains("connection"));
+        assert!(contains("command"));
+        Ok(())
+    }
+}
@@ -0,0 +1,228 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # Capture
+//! The format of the capture files the server records sessions into, so a
+//! session can be replayed later on.
+//!
+//! A capture starts with [`MAGIC`] and the big-endian [`VERSION`], followed
+//! by [`Record`]s framed like the network frames: a 4 byte big-endian length
+//! and the msgpack encoded record.
+
+use crate::{CaptureError, frame::MAX_MESSAGE_SIZE};
+use protocol::{Stamped, command::CommandKind, event::EventKind};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+    time::Duration,
+};
+
+/// Start of every capture file
+pub const MAGIC: &[u8; 8] = b"COTLCAP\0";
+
+/// Version of the capture format, increased on every incompatible change
+pub const VERSION: u32 = 2;
+
+/// A message that was sent or received, with its direction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Message {
+    /// A command received from the client
+    Inbound(CommandKind),
+    /// An event sent to the client, with the tick it was produced on
+    Outbound(Stamped<EventKind>),
+}
+
+/// A single message of a session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Record {
+    /// Microseconds since the recording started
+    pub at: u64,
+    /// Id of the connection the message belongs to
+    pub session: u64,
+    /// The message and its direction
+    pub message: Message,
+}
+
+impl Record {
+    /// Time since the recording started
+    #[must_use]
+    pub const fn elapsed(&self) -> Duration {
+        Duration::from_micros(self.at)
+    }
+}
+
+/// Writes records to a capture
+#[derive(Debug)]
+pub struct CaptureWriter<W: Write> {
+    writer: W,
+}
+
+impl CaptureWriter<BufWriter<File>> {
+    /// Creates a capture file, truncating an existing one
+    ///
+    /// # Errors
+    /// Returns a `CaptureError` when the file can't be created.
+    pub fn create(path: impl AsRef<Path>) -> Result<Self, CaptureError> {
+        Self::new(BufWriter::new(File::create(path)?))
+    }
+}
+
+impl<W: Write> CaptureWriter<W> {
+    /// Starts a capture by writing its header
+    ///
+    /// # Errors
+    /// Returns a `CaptureError` when the header can't be written.
+    pub fn new(mut writer: W) -> Result<Self, CaptureError> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_be_bytes())?;
+        Ok(Self { writer })
+    }
+
+    /// Appends a record to the capture
+    ///
+    /// # Errors
+    /// Returns a `CaptureError` when the record can't be encoded or written.
+    pub fn write(&mut self, record: &Record) -> Result<(), CaptureError> {
+        let data = rmp_serde::to_vec(record)?;
+        let len = u32::try_from(data.len())
+            .ok()
+            .filter(|len| *len <= MAX_MESSAGE_SIZE)
+            .ok_or(CaptureError::RecordTooLarge(data.len()))?;
+
+        self.writer.write_all(&len.to_be_bytes())?;
+        self.writer.write_all(&data)?;
+        Ok(())
+    }
+
+    /// Flushes the underlying writer
+    ///
+    /// # Errors
+    /// Returns a `CaptureError` when flushing fails.
+    pub fn flush(&mut self) -> Result<(), CaptureError> {
+        Ok(self.writer.flush()?)
+    }
+}
+
+/// Reads the records of a capture, in the order they were recorded
+#[derive(Debug)]
+pub struct CaptureReader<R: Read> {
+    reader: R,
+}
+
+impl CaptureReader<BufReader<File>> {
+    /// Opens a capture file
+    ///
+    /// # Errors
+    /// Returns a `CaptureError` when the file can't be read or isn't a
+    /// capture of a supported version.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, CaptureError> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> CaptureReader<R> {
+    /// Reads and checks the header of a capture
+    ///
+    /// # Errors
+    /// Returns a `CaptureError` when the header can't be read or isn't a
+    /// capture of a supported version.
+    pub fn new(mut reader: R) -> Result<Self, CaptureError> {
+        let mut magic = [0; MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(CaptureError::NotACapture);
+        }
+
+        let mut version = [0; 4];
+        reader.read_exact(&mut version)?;
+        let version = u32::from_be_bytes(version);
+        if version != VERSION {
+            return Err(CaptureError::UnsupportedVersion(version));
+        }
+
+        Ok(Self { reader })
+    }
+
+    fn read_record(&mut self) -> Result<Option<Record>, CaptureError> {
+        let mut len = [0; 4];
+        match self.reader.read_exact(&mut len) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e.into()),
+        }
+
+        let len = u32::from_be_bytes(len);
+        if len > MAX_MESSAGE_SIZE {
+            return Err(CaptureError::RecordTooLarge(len as usize));
+        }
+
+        let mut data = vec![0; len as usize];
+        self.reader.read_exact(&mut data)?;
+        Ok(Some(rmp_serde::from_slice(&data)?))
+    }
+}
+
+impl<R: Read> Iterator for CaptureReader<R> {
+    type Item = Result<Record, CaptureError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+/// Reads every record of a capture file
+///
+/// # Errors
+/// Returns a `CaptureError` when the file can't be read or contains an
+/// invalid record.
+pub fn read_file(path: impl AsRef<Path>) -> Result<Vec<Record>, CaptureError> {
+    CaptureReader::open(path)?.collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol::{ServerTick, command::join::Join, event::PlayerJoined};
+
+    #[test]
+    fn records_roundtrip() -> Result<(), CaptureError> {
+        let records = vec![
+            Record {
+                at: 0,
+                session: 1,
+                message: Message::Inbound(CommandKind::Join(Join {
+                    uuid: 42,
+                    hash: 0,
+                    ip: None,
+                    connection: Some(1),
+                    compression: None,
+                    dictionary: None,
+                })),
+            },
+            Record {
+                at: 1500,
+                session: 1,
+                message: Message::Outbound(
+                    ServerTick(3).stamp(EventKind::PlayerJoined(PlayerJoined {})),
+                ),
+            },
+        ];
+
+        let mut writer = CaptureWriter::new(Vec::new())?;
+        for record in &records {
+            writer.write(record)?;
+        }
+        let capture = writer.writer;
+
+        let read = CaptureReader::new(capture.as_slice())?.collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(read, records);
+
+        assert!(matches!(
+            CaptureReader::new(&capture[1..]),
+            Err(CaptureError::NotACapture)
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,186 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # Codec
+//! Defines the wire formats a connection can use to encode commands and events.
+//!
+//! The codec is negotiated with ALPN while establishing the QUIC connection,
+//! or with the `Sec-WebSocket-Protocol` header for WebSocket connections.
+//! Clients that don't ask for a protocol get [`Codec::MessagePack`].
+
+use crate::error::CodecError;
+use serde::{Serialize, de::DeserializeOwned};
+
+/// The wire format used to encode the payload of every frame on a connection
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Codec {
+    /// [MessagePack](https://msgpack.org/), the default
+    #[default]
+    MessagePack,
+    /// JSON, useful for debugging and for languages without a msgpack library
+    Json,
+    /// [postcard](https://postcard.jamesmunns.com/), compact and meant for Rust clients
+    Postcard,
+    /// [bincode](https://github.com/bincode-org/bincode) with its standard configuration
+    Bincode,
+}
+
+impl Codec {
+    /// All codecs, in order of preference of the server
+    pub const ALL: [Self; 4] = [Self::MessagePack, Self::Json, Self::Postcard, Self::Bincode];
+
+    /// The short name of the codec
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::MessagePack => "msgpack",
+            Self::Json => "json",
+            Self::Postcard => "postcard",
+            Self::Bincode => "bincode",
+        }
+    }
+
+    /// The ALPN protocol id a client has to offer to use this codec
+    #[must_use]
+    pub const fn alpn(self) -> &'static [u8] {
+        match self {
+            Self::MessagePack => b"cotl/msgpack",
+            Self::Json => b"cotl/json",
+            Self::Postcard => b"cotl/postcard",
+            Self::Bincode => b"cotl/bincode",
+        }
+    }
+
+    /// Finds the codec belonging to an ALPN protocol id
+    #[must_use]
+    pub fn from_alpn(alpn: &[u8]) -> Option<Self> {
+        Self::ALL.into_iter().find(|codec| codec.alpn() == alpn)
+    }
+
+    /// The `Sec-WebSocket-Protocol` a WebSocket client has to offer to use this codec
+    #[must_use]
+    pub const fn websocket_protocol(self) -> &'static str {
+        match self {
+            Self::MessagePack => "cotl.msgpack",
+            Self::Json => "cotl.json",
+            Self::Postcard => "cotl.postcard",
+            Self::Bincode => "cotl.bincode",
+        }
+    }
+
+    /// Finds the codec belonging to a `Sec-WebSocket-Protocol`
+    #[must_use]
+    pub fn from_websocket_protocol(protocol: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|codec| codec.websocket_protocol() == protocol)
+    }
+
+    /// The ALPN protocol ids of all codecs, used to configure the TLS server
+    #[must_use]
+    pub fn alpn_protocols() -> Vec<Vec<u8>> {
+        Self::ALL
+            .iter()
+            .map(|codec| codec.alpn().to_vec())
+            .collect()
+    }
+
+    /// Encodes a value to bytes
+    ///
+    /// # Errors
+    /// Returns a `CodecError` when the value can't be encoded in this format.
+    pub fn encode<T: Serialize>(self, value: &T) -> Result<Vec<u8>, CodecError> {
+        Ok(match self {
+            Self::MessagePack => rmp_serde::to_vec(value)?,
+            Self::Json => serde_json::to_vec(value)?,
+            Self::Postcard => postcard::to_allocvec(value)?,
+            Self::Bincode => bincode::serde::encode_to_vec(value, bincode::config::standard())?,
+        })
+    }
+
+    /// Decodes a value from bytes
+    ///
+    /// # Errors
+    /// Returns a `CodecError` when the bytes aren't a valid encoding of `T`.
+    pub fn decode<T: DeserializeOwned>(self, data: &[u8]) -> Result<T, CodecError> {
+        Ok(match self {
+            Self::MessagePack => rmp_serde::from_slice(data)?,
+            Self::Json => serde_json::from_slice(data)?,
+            Self::Postcard => postcard::from_bytes(data)?,
+            Self::Bincode => {
+                bincode::serde::decode_from_slice(data, bincode::config::standard())?.0
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol::{
+        Compression,
+        command::{CommandKind, join::Join},
+        event::{EventKind, JoinAccept, PlayerJoined},
+    };
+
+    fn commands() -> Vec<CommandKind> {
+        vec![CommandKind::Join(Join {
+            uuid: 42,
+            hash: 1337,
+            ip: "127.0.0.1:42069".parse().ok(),
+            connection: Some(7),
+            compression: Some(Compression::Zstd),
+            dictionary: None,
+        })]
+    }
+
+    fn events() -> Vec<EventKind> {
+        vec![
+            EventKind::JoinAccept(JoinAccept {
+                connection: 7,
+                uuid: 42,
+                compression: Some(Compression::Lz4),
+                dictionary: Some(3),
+            }),
+            EventKind::PlayerJoined(PlayerJoined {}),
+        ]
+    }
+
+    #[test]
+    fn every_variant_is_tested() {
+        let commands: Vec<_> = commands().iter().map(CommandKind::name).collect();
+        assert_eq!(commands, CommandKind::NAMES);
+        let events: Vec<_> = events().iter().map(EventKind::name).collect();
+        assert_eq!(events, EventKind::NAMES);
+    }
+
+    #[test]
+    fn round_trip_commands() -> Result<(), CodecError> {
+        for codec in Codec::ALL {
+            for command in commands() {
+                let data = codec.encode(&command)?;
+                assert_eq!(codec.decode::<CommandKind>(&data)?, command, "{codec:?}");
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn round_trip_events() -> Result<(), CodecError> {
+        for codec in Codec::ALL {
+            for event in events() {
+                let data = codec.encode(&event)?;
+                assert_eq!(codec.decode::<EventKind>(&data)?, event, "{codec:?}");
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn alpn() {
+        for codec in Codec::ALL {
+            assert_eq!(Codec::from_alpn(codec.alpn()), Some(codec));
+        }
+        assert_eq!(Codec::from_alpn(b"h3"), None);
+    }
+}
@@ -0,0 +1,95 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # Compression
+//! The dictionaries shared by the server and the clients, and the
+//! decompression of compressed frames.
+
+use protocol::Compression;
+use std::{io, path::Path, sync::Arc};
+
+/// A dictionary shared between the server and the clients, used to improve
+/// the compression of small but common payloads.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dictionary {
+    id: u32,
+    data: Arc<[u8]>,
+}
+
+impl Dictionary {
+    /// Creates a dictionary from its raw bytes
+    #[must_use]
+    pub fn new(data: impl Into<Arc<[u8]>>) -> Self {
+        let data = data.into();
+        Self {
+            id: Self::compute_id(&data),
+            data,
+        }
+    }
+
+    /// Reads a dictionary from disk, for example one trained with `zstd --train`.
+    ///
+    /// # Errors
+    /// Returns an `io::Error` when the file can't be read.
+    pub fn read_from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self::new(std::fs::read(path)?))
+    }
+
+    /// The id clients use to tell which dictionary they have.
+    ///
+    /// It is the 32 bit FNV-1a hash of the dictionary bytes.
+    #[must_use]
+    pub const fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// The raw bytes of the dictionary
+    #[must_use]
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    fn compute_id(data: &[u8]) -> u32 {
+        data.iter().fold(0x811c_9dc5, |hash: u32, byte| {
+            (hash ^ u32::from(*byte)).wrapping_mul(0x0100_0193)
+        })
+    }
+}
+
+/// Decompresses the payload of a compressed frame.
+///
+/// # Errors
+/// Returns an `io::Error` when the payload is invalid or would decompress
+/// to more than `max_size` bytes.
+pub fn decompress(
+    compression: Compression,
+    dictionary: Option<&Dictionary>,
+    data: &[u8],
+    max_size: usize,
+) -> io::Result<Vec<u8>> {
+    let dictionary = dictionary.map_or(&[][..], Dictionary::data);
+    match compression {
+        Compression::Zstd => {
+            zstd::bulk::Decompressor::with_dictionary(dictionary)?.decompress(data, max_size)
+        }
+        Compression::Lz4 => {
+            let size = data
+                .get(..4)
+                .and_then(|size| size.try_into().ok())
+                .map(u32::from_le_bytes)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing LZ4 size"))?;
+            if size as usize > max_size {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("decompressed size of {size} bytes is too large"),
+                ));
+            }
+            lz4_flex::block::decompress_size_prepended_with_dict(data, dictionary)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        }
+        _ => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("unsupported compression {compression:?}"),
+        )),
+    }
+}
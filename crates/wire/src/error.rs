@@ -0,0 +1,53 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # Error
+//! Defines the error types for the wire crate
+
+use thiserror::Error;
+
+/// Error type used by [`crate::Codec`]
+#[derive(Debug, Error)]
+pub enum CodecError {
+    /// msgpack `EncodeError`
+    #[error("MessagePack EncodeError: {0}")]
+    MessagePackEncode(#[from] rmp_serde::encode::Error),
+    /// msgpack `DecodeError`
+    #[error("MessagePack DecodeError: {0}")]
+    MessagePackDecode(#[from] rmp_serde::decode::Error),
+    /// JSON error, used for both encoding and decoding
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    /// Postcard error, used for both encoding and decoding
+    #[error("postcard error: {0}")]
+    Postcard(#[from] postcard::Error),
+    /// Bincode `EncodeError`
+    #[error("bincode EncodeError: {0}")]
+    BincodeEncode(#[from] bincode::error::EncodeError),
+    /// Bincode `DecodeError`
+    #[error("bincode DecodeError: {0}")]
+    BincodeDecode(#[from] bincode::error::DecodeError),
+}
+
+/// Error type used when recording or reading a [`crate::capture`]
+#[derive(Debug, Error)]
+pub enum CaptureError {
+    /// Error from IO
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    /// msgpack `EncodeError`
+    #[error("MessagePack EncodeError: {0}")]
+    Encode(#[from] rmp_serde::encode::Error),
+    /// msgpack `DecodeError`
+    #[error("MessagePack DecodeError: {0}")]
+    Decode(#[from] rmp_serde::decode::Error),
+    /// The file doesn't start with the capture header
+    #[error("not a capture file")]
+    NotACapture,
+    /// The capture was written in a format this version can't read
+    #[error("unsupported capture version {0}")]
+    UnsupportedVersion(u32),
+    /// A record is larger than the maximum frame size
+    #[error("record of {0} bytes is too large")]
+    RecordTooLarge(usize),
+}
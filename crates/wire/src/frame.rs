@@ -0,0 +1,12 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # Frame
+//! Every message on a stream is a frame: a 4 byte big-endian length prefix
+//! followed by the payload. These constants are shared by the server and clients.
+
+/// Maximum size of the payload of a single frame
+pub const MAX_MESSAGE_SIZE: u32 = 1024 * 1024;
+
+/// Set in the length prefix of a frame when its payload is compressed
+pub const COMPRESSED_FLAG: u32 = 1 << 31;
@@ -0,0 +1,20 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright (C) 2025 Crypts of the Lost Team
+
+//! # Wire
+//! The wire format shared by the server and the clients: the codecs, the
+//! framing and the compression of the frames, and the capture files.
+
+#![expect(clippy::multiple_crate_versions)]
+
+pub mod capture;
+mod codec;
+pub mod compression;
+mod error;
+pub mod frame;
+
+pub use codec::Codec;
+pub use error::{CaptureError, CodecError};
+
+/// ALPN protocol id used to ask a server for its [`protocol::Status`] instead of joining
+pub const STATUS_ALPN: &[u8] = b"cotl/status";
@@ -0,0 +1,496 @@
+//! # Book
+//! Generates the protocol pages of the book from the JSON Schema of the
+//! protocol, so they can't drift from the types in the `protocol` crate.
+
+use protocol::{command::CommandKind, event::EventKind, schema::protocol_schema};
+use serde_json::{Map, Value, json};
+use std::{
+    error::Error,
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// A page of the book
+#[derive(Debug)]
+pub struct Page {
+    pub path: PathBuf,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Command,
+    Event,
+}
+
+impl Kind {
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Command => "Command",
+            Self::Event => "Event",
+        }
+    }
+
+    const fn directory(self) -> &'static str {
+        match self {
+            Self::Command => "command",
+            Self::Event => "event",
+        }
+    }
+
+    const fn enum_name(self) -> &'static str {
+        match self {
+            Self::Command => "CommandKind",
+            Self::Event => "EventKind",
+        }
+    }
+
+    const fn introduction(self) -> &'static str {
+        match self {
+            Self::Command => {
+                "A command is a message sent **from the client to the server**. It is used\n\
+                 by the client to send specific commands or requests to the server, starting\n\
+                 with `Join`."
+            }
+            Self::Event => {
+                "An event is a message sent **from the server to the client**. It is used by\n\
+                 the server to send game or system events to the client, starting with\n\
+                 `JoinAccept`."
+            }
+        }
+    }
+
+    /// Encodes an example the same way the server does with msgpack
+    fn encode(self, variant: &str, example: &Value) -> Result<Vec<u8>, Box<dyn Error>> {
+        let message = json!({ variant: example });
+        Ok(match self {
+            Self::Command => rmp_serde::to_vec(&serde_json::from_value::<CommandKind>(message)?)?,
+            Self::Event => rmp_serde::to_vec(&serde_json::from_value::<EventKind>(message)?)?,
+        })
+    }
+}
+
+/// A variant of `CommandKind` or `EventKind`
+#[derive(Debug)]
+struct Variant {
+    name: String,
+    description: String,
+    definition: String,
+}
+
+/// Generates all protocol pages, together with the `SUMMARY.md` listing them.
+pub fn pages() -> Result<Vec<Page>, Box<dyn Error>> {
+    let schema = protocol_schema();
+    let definitions = schema["$defs"]
+        .as_object()
+        .ok_or("the schema has no definitions")?;
+
+    let mut pages = Vec::new();
+    let mut summary = String::new();
+    for kind in [Kind::Command, Kind::Event] {
+        let variants = variants(definitions, kind)?;
+        pages.push(overview(kind, &variants));
+        writeln!(
+            summary,
+            "  - [{}](./protocol/{}.md)",
+            kind.name(),
+            kind.directory()
+        )?;
+
+        for variant in &variants {
+            let definition = &definitions[&variant.definition];
+            pages.push(message(kind, variant, definition, definitions)?);
+            writeln!(
+                summary,
+                "    - [{}](./protocol/{}/{}.md)",
+                variant.name,
+                kind.directory(),
+                file_name(&variant.name)
+            )?;
+        }
+    }
+    pages.push(self::summary(&summary)?);
+
+    Ok(pages)
+}
+
+/// Writes all pages to disk
+pub fn write() -> Result<(), Box<dyn Error>> {
+    for page in pages()? {
+        fs::write(root().join(&page.path), page.content)?;
+    }
+    Ok(())
+}
+
+/// Returns the pages that differ from what is on disk
+pub fn outdated() -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    Ok(pages()?
+        .into_iter()
+        .filter(|page| {
+            fs::read_to_string(root().join(&page.path)).ok().as_ref() != Some(&page.content)
+        })
+        .map(|page| page.path)
+        .collect())
+}
+
+/// The root of the repository, the paths of the pages are relative to it
+fn root() -> &'static Path {
+    Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/.."))
+}
+
+fn variants(definitions: &Map<String, Value>, kind: Kind) -> Result<Vec<Variant>, Box<dyn Error>> {
+    let one_of = definitions[kind.enum_name()]["oneOf"]
+        .as_array()
+        .ok_or("messages have to be an externally tagged enum")?;
+
+    one_of
+        .iter()
+        .map(|variant| {
+            let (name, schema) = variant["properties"]
+                .as_object()
+                .and_then(|properties| properties.iter().next())
+                .ok_or("variants have to hold a struct")?;
+            Ok(Variant {
+                name: name.clone(),
+                description: description(variant),
+                definition: reference(schema).ok_or("variants have to hold a struct")?,
+            })
+        })
+        .collect()
+}
+
+fn overview(kind: Kind, variants: &[Variant]) -> Page {
+    let rows = variants
+        .iter()
+        .map(|variant| {
+            vec![
+                format!("`{}`", variant.name),
+                variant.description.replace('\n', " "),
+                format!(
+                    "Holds [{}](./{}/{}.md)",
+                    variant.name,
+                    kind.directory(),
+                    file_name(&variant.name)
+                ),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    let content = format!(
+        "{GENERATED}\n# {}\n\n{}\n\n{}",
+        kind.name(),
+        kind.introduction(),
+        table(&["Variant", "Description", "Data"], &rows)
+    );
+
+    Page {
+        path: PathBuf::from(format!("docs/protocol/{}.md", kind.directory())),
+        content,
+    }
+}
+
+fn message(
+    kind: Kind,
+    variant: &Variant,
+    definition: &Value,
+    definitions: &Map<String, Value>,
+) -> Result<Page, Box<dyn Error>> {
+    let mut content = format!("{GENERATED}\n# {}\n\n", variant.name);
+    writeln!(content, "{}\n", description(definition))?;
+
+    let since = definition["x-since"]
+        .as_u64()
+        .ok_or_else(|| format!("{} has no `x-since`", variant.name))?;
+    writeln!(content, "Introduced in protocol version {since}.\n")?;
+
+    match kind {
+        Kind::Command => writeln!(content, "Sent by the client to the server.\n")?,
+        Kind::Event => {
+            let target = definition["x-target"]
+                .as_str()
+                .ok_or_else(|| format!("{} has no `x-target`", variant.name))?;
+            writeln!(content, "Sent by the server to: {target}.\n")?;
+        }
+    }
+
+    let required = definition["required"]
+        .as_array()
+        .map(|required| {
+            required
+                .iter()
+                .filter_map(Value::as_str)
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    let rows = definition["properties"]
+        .as_object()
+        .map(|properties| {
+            properties
+                .iter()
+                .map(|(name, schema)| {
+                    vec![
+                        format!("`{name}`"),
+                        format!("`{}`", type_name(schema)),
+                        if required.contains(&name.as_str()) {
+                            "yes"
+                        } else {
+                            "no"
+                        }
+                        .to_owned(),
+                        field_description(schema, definitions),
+                    ]
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    if rows.is_empty() {
+        writeln!(
+            content,
+            "This {} has no fields.\n",
+            kind.name().to_lowercase()
+        )?;
+    } else {
+        writeln!(
+            content,
+            "{}",
+            table(&["Field", "Type", "Required", "Description"], &rows)
+        )?;
+    }
+
+    let example = definition["examples"]
+        .get(0)
+        .ok_or_else(|| format!("{} has no example", variant.name))?;
+    let encoded = kind.encode(&variant.name, example)?;
+    writeln!(content, "## Example\n")?;
+    writeln!(
+        content,
+        "The msgpack payload of a frame holding this {}:\n",
+        kind.name().to_lowercase()
+    )?;
+    writeln!(content, "```text\n{}\n```\n", hex(&encoded))?;
+    writeln!(
+        content,
+        "The same {} as JSON:\n",
+        kind.name().to_lowercase()
+    )?;
+    writeln!(
+        content,
+        "```json\n{}\n```",
+        serde_json::to_string_pretty(&json!({ &variant.name: example }))?
+    )?;
+
+    Ok(Page {
+        path: PathBuf::from(format!(
+            "docs/protocol/{}/{}.md",
+            kind.directory(),
+            file_name(&variant.name)
+        )),
+        content,
+    })
+}
+
+fn summary(protocol: &str) -> Result<Page, Box<dyn Error>> {
+    let path = PathBuf::from("docs/SUMMARY.md");
+    let current = fs::read_to_string(root().join(&path))?;
+
+    let mut content = String::new();
+    let mut skipping = false;
+    for line in current.lines() {
+        if skipping && line.starts_with("  ") {
+            continue;
+        }
+        skipping = false;
+        writeln!(content, "{line}")?;
+        if line.starts_with("- [Protocol]") {
+            content.push_str(protocol);
+            skipping = true;
+        }
+    }
+
+    Ok(Page { path, content })
+}
+
+const GENERATED: &str = "<!-- generated by `cargo xtask book`, don't edit by hand -->\n";
+
+/// Turns `JoinAccept` into `join_accept`
+fn file_name(name: &str) -> String {
+    let mut file_name = String::new();
+    for (i, c) in name.char_indices() {
+        if c.is_uppercase() && i != 0 {
+            file_name.push('_');
+        }
+        file_name.push(c.to_ascii_lowercase());
+    }
+    file_name
+}
+
+fn reference(schema: &Value) -> Option<String> {
+    schema["$ref"]
+        .as_str()
+        .and_then(|reference| reference.rsplit('/').next())
+        .map(str::to_owned)
+}
+
+/// The description of a schema, with intra doc links reduced to their text
+fn description(schema: &Value) -> String {
+    let description = schema["description"].as_str().unwrap_or_default();
+    let mut result = String::new();
+    let mut rest = description;
+    while let Some(start) = rest.find("](crate::") {
+        let Some(end) = rest[start..].find(')') else {
+            break;
+        };
+        let text = &rest[..start];
+        let text = text.rfind('[').map_or(text, |open| {
+            result.push_str(&text[..open]);
+            &text[open + 1..]
+        });
+        result.push_str(text);
+        rest = &rest[start + end + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+fn field_description(schema: &Value, definitions: &Map<String, Value>) -> String {
+    let mut description = description(schema).replace('\n', " ");
+
+    let inner = schema["anyOf"]
+        .as_array()
+        .and_then(|any_of| any_of.iter().find_map(reference))
+        .or_else(|| reference(schema));
+    let values = inner
+        .and_then(|name| definitions.get(&name))
+        .and_then(|definition| definition["oneOf"].as_array())
+        .map(|one_of| {
+            one_of
+                .iter()
+                .filter_map(|value| value["const"].as_str())
+                .map(|value| format!("`{value}`"))
+                .collect::<Vec<_>>()
+        })
+        .filter(|values| !values.is_empty());
+    if let Some(values) = values {
+        let _ = write!(description, ", one of {}", values.join(", "));
+    }
+
+    description
+}
+
+/// The Rust type of a schema
+fn type_name(schema: &Value) -> String {
+    if let Some(reference) = reference(schema) {
+        return reference;
+    }
+
+    if let Some(any_of) = schema["anyOf"].as_array() {
+        let nullable = any_of.iter().any(|schema| schema["type"] == "null");
+        if let Some(inner) = any_of.iter().find(|schema| schema["type"] != "null") {
+            let inner = type_name(inner);
+            return if nullable {
+                format!("Option<{inner}>")
+            } else {
+                inner
+            };
+        }
+    }
+
+    let (ty, nullable) = match &schema["type"] {
+        Value::Array(types) => (
+            types
+                .iter()
+                .find(|ty| *ty != "null")
+                .cloned()
+                .unwrap_or_default(),
+            types.iter().any(|ty| ty == "null"),
+        ),
+        ty => (ty.clone(), false),
+    };
+
+    let ty = match (ty.as_str(), schema["format"].as_str()) {
+        (Some("integer"), Some(format)) => format.replace("uint", "u").replace("int", "i"),
+        (Some("integer"), None) => "i64".to_owned(),
+        (Some("number"), _) => "f64".to_owned(),
+        (Some("string"), _) => "String".to_owned(),
+        (Some("boolean"), _) => "bool".to_owned(),
+        (Some("array"), _) => {
+            let items = type_name(&schema["items"]);
+            if schema["uniqueItems"] == true {
+                format!("HashSet<{items}>")
+            } else {
+                format!("Vec<{items}>")
+            }
+        }
+        _ => "object".to_owned(),
+    };
+
+    if nullable {
+        format!("Option<{ty}>")
+    } else {
+        ty
+    }
+}
+
+/// Renders a markdown table with aligned columns
+fn table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let widths = header
+        .iter()
+        .enumerate()
+        .map(|(i, title)| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .chain([title.len()])
+                .max()
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+
+    let line = |cells: Vec<String>| {
+        let cells = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect::<Vec<_>>();
+        format!("| {} |\n", cells.join(" | "))
+    };
+
+    let mut table = line(header.iter().map(|title| (*title).to_owned()).collect());
+    table.push_str(&line(
+        widths.iter().map(|width| "-".repeat(*width)).collect(),
+    ));
+    for row in rows {
+        table.push_str(&line(row.clone()));
+    }
+    table
+}
+
+/// Formats bytes as hex, 16 per line
+fn hex(data: &[u8]) -> String {
+    data.chunks(16)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn committed_pages_are_up_to_date() -> Result<(), Box<dyn Error>> {
+        let outdated = outdated()?;
+        assert!(
+            outdated.is_empty(),
+            "outdated protocol pages, run `cargo xtask book`: {outdated:?}"
+        );
+        Ok(())
+    }
+}
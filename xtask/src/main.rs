@@ -20,6 +20,10 @@
 //! along with this program.  If not, see <https://www.gnu.org/licenses/>.
 //! ```
 
+#![expect(clippy::multiple_crate_versions)]
+
+mod book;
+
 use clap::{Parser, Subcommand};
 use std::process::Command;
 
@@ -39,6 +43,10 @@ enum SubCommands {
     Fmt,
     // Runs tests using nextest
     Test,
+    // Writes the JSON Schema of the protocol to `docs/protocol/schema.json`
+    Schema,
+    // Generates the protocol pages of the book from the protocol types
+    Book,
 }
 
 #[expect(clippy::print_stdout)]
@@ -89,6 +97,12 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 }
             }
 
+            let outdated = book::outdated()?;
+            if !outdated.is_empty() {
+                println!("outdated protocol pages, run `cargo xtask book`: {outdated:?}");
+                std::process::exit(101);
+            }
+
             println!("\n\x1b[32;1m{:>12}\x1b[0m all checks", "Passed");
         }
         SubCommands::Fmt => {
@@ -123,6 +137,17 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 std::process::exit(101);
             }
         }
+        SubCommands::Schema => {
+            let path = "docs/protocol/schema.json";
+            std::fs::write(path, protocol::schema::protocol_schema_string()?)?;
+
+            println!("\n\x1b[32;1m{:>12}\x1b[0m {path}", "Wrote");
+        }
+        SubCommands::Book => {
+            book::write()?;
+
+            println!("\n\x1b[32;1m{:>12}\x1b[0m protocol pages", "Generated");
+        }
     }
 
     Ok(())